@@ -0,0 +1,141 @@
+use crate::renderer::scene::Scene;
+use crate::renderer::RenderParams;
+use egui::{Context, Key, Modifiers, Vec2};
+use std::time::{Duration, Instant};
+
+/// Oldest undo steps are forgotten beyond this
+const MAX_STEPS: usize = 100;
+/// Scrolling is one edit until the wheel rests this long, its events span many frames
+const SCROLL_SETTLE_TIME: Duration = Duration::from_millis(300);
+
+#[derive(Clone)]
+struct Snapshot {
+    scene: Scene,
+    params: RenderParams,
+}
+
+///
+/// Undo/redo of scene and parameter edits. Instead of recording every widget change the
+/// state is compared with the last committed snapshot, and changes are committed only once
+/// the user lets go of the mouse and keyboard, so a whole slider drag is a single step.
+///
+pub struct EditHistory {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    committed: Snapshot,
+    scrolled_at: Option<Instant>,
+}
+
+impl EditHistory {
+    pub fn new(scene: &Scene, params: &RenderParams) -> Self {
+        Self {
+            undo: vec![],
+            redo: vec![],
+            committed: Snapshot {
+                scene: scene.clone(),
+                params: params.clone(),
+            },
+            scrolled_at: None,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    ///
+    /// Record the current state as an undo step if it changed and no edit is in progress,
+    /// handles Ctrl+Z / Ctrl+Shift+Z (Ctrl+Y). Call once per frame after the UI was built.
+    /// Nothing is recorded while `animating` (playback or a sequence render moves through
+    /// frames), the frame it stops at becomes a single step.
+    ///
+    pub fn update(
+        &mut self,
+        ctx: &Context,
+        scene: &mut Scene,
+        params: &mut RenderParams,
+        animating: bool,
+    ) {
+        // text fields have their own undo and keep focus while typing
+        let typing = ctx.wants_keyboard_input();
+        let editing = {
+            let input = ctx.input();
+            if input.scroll_delta != Vec2::ZERO {
+                self.scrolled_at = Some(Instant::now());
+            }
+            let scrolling = self
+                .scrolled_at
+                .is_some_and(|scrolled_at| scrolled_at.elapsed() < SCROLL_SETTLE_TIME);
+            input.pointer.any_down() || !input.keys_down.is_empty() || scrolling
+        };
+        if !typing && !editing && !animating {
+            self.commit(scene, params);
+        }
+
+        if typing {
+            return;
+        }
+        let shift_command = Modifiers {
+            shift: true,
+            ..Modifiers::COMMAND
+        };
+        let (undo, redo) = {
+            let mut input = ctx.input_mut();
+            let undo = input.consume_key(Modifiers::COMMAND, Key::Z);
+            let redo = input.consume_key(shift_command, Key::Z) ||
+                input.consume_key(Modifiers::COMMAND, Key::Y);
+            (undo, redo)
+        };
+        if undo {
+            self.undo(scene, params);
+        } else if redo {
+            self.redo(scene, params);
+        }
+    }
+
+    fn commit(&mut self, scene: &Scene, params: &RenderParams) {
+        if self.committed.scene == *scene && self.committed.params == *params {
+            return;
+        }
+        let previous = std::mem::replace(
+            &mut self.committed,
+            Snapshot {
+                scene: scene.clone(),
+                params: params.clone(),
+            },
+        );
+        self.undo.push(previous);
+        if self.undo.len() > MAX_STEPS {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, scene: &mut Scene, params: &mut RenderParams) {
+        // pending edit becomes its own step so redo can bring it back
+        self.commit(scene, params);
+        if let Some(snapshot) = self.undo.pop() {
+            self.redo
+                .push(std::mem::replace(&mut self.committed, snapshot));
+            self.restore(scene, params);
+        }
+    }
+
+    pub fn redo(&mut self, scene: &mut Scene, params: &mut RenderParams) {
+        self.commit(scene, params);
+        if let Some(snapshot) = self.redo.pop() {
+            self.undo
+                .push(std::mem::replace(&mut self.committed, snapshot));
+            self.restore(scene, params);
+        }
+    }
+
+    fn restore(&self, scene: &mut Scene, params: &mut RenderParams) {
+        *scene = self.committed.scene.clone();
+        *params = self.committed.params.clone();
+    }
+}
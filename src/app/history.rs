@@ -0,0 +1,183 @@
+use crate::renderer::frame::Frame;
+use crate::renderer::pass::RenderPass;
+use crate::renderer::scene::Scene;
+use crate::renderer::RenderParams;
+use egui::{ImageButton, TextureFilter, TextureHandle, TextureId, Ui};
+
+/// Oldest renders are dropped once the history grows over this
+const MAX_ENTRIES: usize = 20;
+const THUMBNAIL_HEIGHT: f32 = 60.0;
+
+///
+/// Finished render together with everything needed to reproduce it
+///
+pub struct HistoryEntry {
+    /// Stable id shown in the UI, indices shift when old entries are dropped
+    pub(crate) number: usize,
+    pub(crate) frame: Frame,
+    pub(crate) params: RenderParams,
+    pub(crate) scene: Scene,
+    thumbnail: TextureHandle,
+}
+
+pub enum HistoryAction {
+    /// Display the render of the entry with this number
+    Show(usize),
+    /// Bring back parameters and scene the entry was rendered with
+    Restore(usize),
+}
+
+///
+/// Strip of previous renders and the split view comparing one of them with the shown image
+///
+pub struct RenderHistory {
+    entries: Vec<HistoryEntry>,
+    next_number: usize,
+    /// Entry currently displayed, `None` while showing a render in progress
+    pub(crate) shown: Option<usize>,
+    /// Entry drawn left of the split line
+    pub(crate) compare_with: Option<usize>,
+    /// Position of the split line as fraction of the image width
+    pub(crate) split: f32,
+    comparison: Option<(usize, RenderPass, TextureHandle)>,
+}
+
+impl RenderHistory {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            next_number: 1,
+            shown: None,
+            compare_with: None,
+            split: 0.5,
+            comparison: None,
+        }
+    }
+
+    pub fn get(&self, number: usize) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|entry| entry.number == number)
+    }
+
+    pub fn push(&mut self, ui: &Ui, frame: &Frame, params: RenderParams, scene: Scene) {
+        let number = self.next_number;
+        self.next_number += 1;
+        let thumbnail = ui.ctx().load_texture(
+            format!("history-{}", number),
+            frame.image.clone(),
+            TextureFilter::Linear,
+        );
+        self.entries.push(HistoryEntry {
+            number,
+            frame: frame.clone(),
+            params,
+            scene,
+            thumbnail,
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            let removed = self.entries.remove(0);
+            if self.compare_with == Some(removed.number) {
+                self.compare_with = None;
+            }
+        }
+        self.shown = Some(number);
+    }
+
+    ///
+    /// Thumbnails of previous renders, click shows the render, right click offers more
+    ///
+    pub fn show_strip(&mut self, ui: &mut Ui) -> Option<HistoryAction> {
+        if self.entries.is_empty() {
+            return None;
+        }
+
+        let mut action = None;
+        ui.horizontal(|ui| {
+            ui.label("Compare with");
+            egui::ComboBox::from_id_source("compare-with")
+                .selected_text(match self.compare_with {
+                    Some(number) => format!("#{}", number),
+                    None => "nothing".to_string(),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.compare_with, None, "nothing");
+                    for entry in &self.entries {
+                        ui.selectable_value(
+                            &mut self.compare_with,
+                            Some(entry.number),
+                            format!("#{}", entry.number),
+                        )
+                        .on_hover_text(describe(&entry.params));
+                    }
+                });
+            ui.add_enabled(
+                self.compare_with.is_some(),
+                egui::Slider::new(&mut self.split, 0.0..=1.0).text("Split"),
+            );
+        });
+
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                for entry in self.entries.iter().rev() {
+                    let size = entry.thumbnail.size_vec2();
+                    let width = size.x * THUMBNAIL_HEIGHT / size.y;
+                    let response = ui
+                        .add(
+                            ImageButton::new(entry.thumbnail.id(), [width, THUMBNAIL_HEIGHT])
+                                .selected(self.shown == Some(entry.number)),
+                        )
+                        .on_hover_text(format!("#{}: {}", entry.number, describe(&entry.params)));
+                    if response.clicked() {
+                        action = Some(HistoryAction::Show(entry.number));
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button("Compare with shown image").clicked() {
+                            self.compare_with = Some(entry.number);
+                            ui.close_menu();
+                        }
+                        if ui.button("Restore parameters and scene").clicked() {
+                            action = Some(HistoryAction::Restore(entry.number));
+                            ui.close_menu();
+                        }
+                    });
+                }
+            });
+        });
+        action
+    }
+
+    ///
+    /// Texture with the given pass of the entry chosen for comparison
+    ///
+    pub fn comparison_texture(&mut self, ui: &Ui, pass: RenderPass) -> Option<TextureId> {
+        let number = self.compare_with?;
+        let cached = matches!(&self.comparison, Some((n, p, _)) if *n == number && *p == pass);
+        if !cached {
+            let entry = self.entries.iter().find(|entry| entry.number == number)?;
+            let texture = ui.ctx().load_texture(
+                "comparison",
+                pass.image(&entry.frame),
+                TextureFilter::Linear,
+            );
+            self.comparison = Some((number, pass, texture));
+        }
+        self.comparison.as_ref().map(|(_, _, texture)| texture.id())
+    }
+}
+
+/// Short summary of the parameters that matter for image quality
+pub fn describe(params: &RenderParams) -> String {
+    let mut description = format!(
+        "{}, {} spp, {}, {} filter",
+        params.render_resolution(),
+        params.samples,
+        params.sampler,
+        params.filter.kind
+    );
+    if params.adaptive_sampling {
+        description += ", adaptive";
+    }
+    if params.denoise {
+        description += ", denoised";
+    }
+    description
+}
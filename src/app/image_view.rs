@@ -0,0 +1,121 @@
+use egui::{pos2, Pos2, Rect, TextureFilter, Vec2};
+
+const MIN_ZOOM: f32 = 0.05;
+const MAX_ZOOM: f32 = 64.0;
+/// Screen pixels per image pixel from which the image is shown without smoothing
+const NEAREST_FILTER_ZOOM: f32 = 2.0;
+
+///
+/// What dragging and scrolling over the rendered image does
+///
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ImageInteraction {
+    /// Move the scene camera
+    Camera,
+    /// Zoom and pan the image itself
+    Inspect,
+    /// Select the part of the image to render
+    Region,
+}
+
+///
+/// Placement of the rendered image in the preview pane
+///
+pub struct ImageView {
+    /// Screen points per image pixel, `None` fits the whole image into the pane
+    zoom: Option<f32>,
+    /// Image position (in pixels) shown in the center of the pane
+    center: Vec2,
+}
+
+impl ImageView {
+    pub fn new() -> Self {
+        Self {
+            zoom: None,
+            center: Vec2::ZERO,
+        }
+    }
+
+    pub fn fit(&mut self) {
+        self.zoom = None;
+    }
+
+    /// Show one image pixel per physical screen pixel
+    pub fn actual_size(&mut self, pixels_per_point: f32, image_size: Vec2) {
+        self.zoom = Some(1.0 / pixels_per_point);
+        self.center = image_size / 2.0;
+    }
+
+    pub fn zoom(&self, pane: Rect, image_size: Vec2) -> f32 {
+        self.zoom.unwrap_or_else(|| {
+            (pane.width() / image_size.x)
+                .min(pane.height() / image_size.y)
+                .max(f32::MIN_POSITIVE)
+        })
+    }
+
+    ///
+    /// Screen rectangle covered by the whole image, it can extend outside of the pane
+    ///
+    pub fn image_rect(&self, pane: Rect, image_size: Vec2) -> Rect {
+        let zoom = self.zoom(pane, image_size);
+        let center = match self.zoom {
+            Some(_) => self.center,
+            None => image_size / 2.0,
+        };
+        Rect::from_min_size(pane.center() - center * zoom, image_size * zoom)
+    }
+
+    ///
+    /// Multiply the zoom by `factor` keeping the image point under `pointer` in place
+    ///
+    pub fn zoom_at(&mut self, factor: f32, pointer: Pos2, pane: Rect, image_size: Vec2) {
+        let rect = self.image_rect(pane, image_size);
+        let zoom = self.zoom(pane, image_size);
+        let anchor = (pointer - rect.min) / zoom;
+        let new_zoom = (zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.zoom = Some(new_zoom);
+        self.center = anchor + (pane.center() - pointer) / new_zoom;
+    }
+
+    pub fn pan(&mut self, delta: Vec2, pane: Rect, image_size: Vec2) {
+        if self.zoom.is_none() {
+            self.zoom = Some(self.zoom(pane, image_size));
+            self.center = image_size / 2.0;
+        }
+        self.center -= delta / self.zoom(pane, image_size);
+    }
+
+    /// Texture filter suitable for the current zoom, pixels stay sharp when magnified
+    pub fn filter(&self, pane: Rect, image_size: Vec2, pixels_per_point: f32) -> TextureFilter {
+        match self.zoom(pane, image_size) * pixels_per_point >= NEAREST_FILTER_ZOOM {
+            true => TextureFilter::Nearest,
+            false => TextureFilter::Linear,
+        }
+    }
+}
+
+///
+/// Position in [0, 1]² of the screen point within the image, `None` outside of it
+///
+pub fn image_uv(rect: Rect, position: Pos2) -> Option<Pos2> {
+    if !rect.contains(position) {
+        return None;
+    }
+    let uv = (position - rect.min) / rect.size();
+    Some(pos2(uv.x, uv.y))
+}
+
+/// Like [`image_uv`] but clamps points outside of the image to its border
+pub fn clamped_uv(rect: Rect, position: Pos2) -> Pos2 {
+    let uv = (position.clamp(rect.min, rect.max) - rect.min) / rect.size();
+    pos2(uv.x, uv.y)
+}
+
+/// Pixel of an image of the given size at `uv`
+pub fn uv_to_pixel(uv: Pos2, size: [usize; 2]) -> [usize; 2] {
+    [
+        ((uv.x * size[0] as f32) as usize).min(size[0] - 1),
+        ((uv.y * size[1] as f32) as usize).min(size[1] - 1),
+    ]
+}
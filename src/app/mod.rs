@@ -1,19 +1,74 @@
+use crate::app::edit_history::EditHistory;
+use crate::app::outliner::Outliner;
+use crate::app::resolution_editor::ResolutionEditor;
+use crate::app::scene_files::SceneFiles;
+use crate::app::timeline::Timeline;
 use crate::app::ui::RenderBox;
+use crate::renderer::scene::Scene;
 use crate::renderer::RenderParams;
+use serde::{Deserialize, Serialize};
 use std::time::Instant;
 
+mod edit_history;
+mod history;
+mod image_view;
+mod navigation;
+mod outliner;
+mod resolution_editor;
+pub(crate) mod scene_files;
+mod timeline;
 mod ui;
 
 pub struct MyApp {
     render_box: RenderBox,
     params: RenderParams,
+    outliner: Outliner,
+    resolution_editor: ResolutionEditor,
+    scene_files: SceneFiles,
+    edits: EditHistory,
+    timeline: Timeline,
+}
+
+///
+/// Part of the application state kept between sessions, window and panel layout is
+/// persisted by eframe itself
+///
+#[derive(Serialize, Deserialize)]
+struct SavedState {
+    scene: Scene,
+    params: RenderParams,
+    scene_files: SceneFiles,
+}
+
+impl MyApp {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self::default();
+        let saved = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<SavedState>(storage, eframe::APP_KEY));
+        if let Some(saved) = saved {
+            app.render_box.scene = saved.scene;
+            app.params = saved.params;
+            app.scene_files = saved.scene_files;
+            app.edits = EditHistory::new(&app.render_box.scene, &app.params);
+        }
+        app
+    }
 }
 
 impl Default for MyApp {
     fn default() -> Self {
+        let render_box = RenderBox::new();
+        let params = RenderParams::default();
+        let edits = EditHistory::new(&render_box.scene, &params);
         Self {
-            render_box: RenderBox::new(),
-            params: RenderParams::default(),
+            render_box,
+            params,
+            outliner: Outliner::new(),
+            resolution_editor: ResolutionEditor::new(),
+            scene_files: SceneFiles::new(),
+            edits,
+            timeline: Timeline::new(),
         }
     }
 }
@@ -0,0 +1,59 @@
+use crate::renderer::camera::CameraView;
+use egui::{Key, PointerButton, Response, Ui};
+
+/// Radians of rotation per point of mouse movement
+const ROTATE_SPEED: f64 = 0.008;
+const DOLLY_SPEED: f64 = 0.002;
+/// Scene units per second when flying with the keyboard
+const FLY_SPEED: f64 = 1.5;
+
+///
+/// Camera controls of the preview image:
+/// left drag orbits around the target, right/middle or shift + left drag pans,
+/// ctrl + left drag looks around, scroll dollies and WASD/QE fly while hovering the image.
+/// `pan_scale` converts points on screen to offsets at unit distance from the camera.
+/// Returns true if the view changed.
+///
+pub fn navigate(ui: &Ui, response: &Response, view: &mut CameraView, pan_scale: f64) -> bool {
+    let before = *view;
+    // taken before `input` which keeps the context locked
+    let keyboard_free = !ui.ctx().wants_keyboard_input();
+    let input = ui.input();
+    let delta = response.drag_delta();
+    let (dx, dy) = (delta.x as f64, delta.y as f64);
+
+    if response.dragged_by(PointerButton::Primary) {
+        if input.modifiers.shift {
+            view.pan(-dx * pan_scale, dy * pan_scale);
+        } else if input.modifiers.command {
+            view.look(-dx * ROTATE_SPEED, -dy * ROTATE_SPEED);
+        } else {
+            view.orbit(-dx * ROTATE_SPEED, dy * ROTATE_SPEED);
+        }
+    }
+    if response.dragged_by(PointerButton::Secondary) || response.dragged_by(PointerButton::Middle) {
+        view.pan(-dx * pan_scale, dy * pan_scale);
+    }
+
+    if response.hovered() {
+        let scroll = input.scroll_delta.y as f64;
+        if scroll != 0.0 {
+            view.dolly((-scroll * DOLLY_SPEED).exp());
+        }
+
+        if keyboard_free {
+            let axis = |positive: Key, negative: Key| {
+                input.key_down(positive) as i32 as f64 - input.key_down(negative) as i32 as f64
+            };
+            let step = FLY_SPEED * input.stable_dt as f64;
+            let right = axis(Key::D, Key::A);
+            let up = axis(Key::E, Key::Q);
+            let forward = axis(Key::W, Key::S);
+            if right != 0.0 || up != 0.0 || forward != 0.0 {
+                view.fly(right * step, up * step, forward * step);
+            }
+        }
+    }
+
+    *view != before
+}
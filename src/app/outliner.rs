@@ -0,0 +1,552 @@
+use crate::math::Point3;
+use crate::renderer::hittable::Hittable;
+use crate::renderer::scene::csg::CsgOperation;
+use crate::renderer::scene::instanced::Instanced;
+use crate::renderer::scene::sdf::SdfShape;
+use crate::renderer::scene::transformed::Transform;
+use crate::renderer::scene::voxel_volume::{VoxelGrid, VoxelSource, VoxelVolume};
+use crate::renderer::scene::{ObjectType, Scene, SceneObject};
+use crate::{Color3, Vec3};
+use egui::{DragValue, Id, Ui};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+enum OutlinerAction {
+    SetVisible(usize, bool),
+    Remove(usize),
+    Duplicate(usize),
+    Move(usize, isize),
+}
+
+///
+/// Scene contents panel: list of objects with visibility toggles and editor of the
+/// selected object
+///
+pub struct Outliner {
+    pub(crate) selected: Option<usize>,
+    /// Copy of the selected object the editor works on, with the object it was made from
+    editing: Option<(Arc<SceneObject>, SceneObject)>,
+}
+
+impl Outliner {
+    pub fn new() -> Self {
+        Self {
+            selected: None,
+            editing: None,
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, scene: &mut Scene) {
+        ui.menu_button("Add object", |ui| {
+            for object_type in ObjectType::ALL {
+                if ui.button(format!("{}", object_type)).clicked() {
+                    self.selected = Some(scene.add(object_type));
+                    ui.close_menu();
+                }
+            }
+        });
+
+        let mut action = None;
+        for (index, node) in scene.contents().iter().enumerate() {
+            ui.push_id(index, |ui| {
+                ui.horizontal(|ui| {
+                    let mut visible = node.visible;
+                    if ui
+                        .checkbox(&mut visible, "")
+                        .on_hover_text("Visible in render")
+                        .changed()
+                    {
+                        action = Some(OutlinerAction::SetVisible(index, visible));
+                    }
+                    if ui
+                        .selectable_label(self.selected == Some(index), &node.name)
+                        .on_hover_text(node.object.name())
+                        .clicked()
+                    {
+                        self.selected = Some(index);
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✖").on_hover_text("Delete").clicked() {
+                            action = Some(OutlinerAction::Remove(index));
+                        }
+                        if ui.small_button("⧉").on_hover_text("Duplicate").clicked() {
+                            action = Some(OutlinerAction::Duplicate(index));
+                        }
+                        if ui.small_button("⏷").on_hover_text("Move down").clicked() {
+                            action = Some(OutlinerAction::Move(index, 1));
+                        }
+                        if ui.small_button("⏶").on_hover_text("Move up").clicked() {
+                            action = Some(OutlinerAction::Move(index, -1));
+                        }
+                    });
+                });
+            });
+        }
+
+        match action {
+            Some(OutlinerAction::SetVisible(index, visible)) => {
+                scene.contents_mut()[index].visible = visible;
+            }
+            Some(OutlinerAction::Remove(index)) => {
+                scene.remove(index);
+                self.selected = match self.selected {
+                    Some(selected) if selected == index => None,
+                    Some(selected) if selected > index => Some(selected - 1),
+                    other => other,
+                };
+            }
+            Some(OutlinerAction::Duplicate(index)) => {
+                self.selected = Some(scene.duplicate(index));
+            }
+            Some(OutlinerAction::Move(index, offset)) => {
+                let moved_to = scene.move_object(index, offset);
+                if self.selected == Some(index) {
+                    self.selected = Some(moved_to);
+                } else if self.selected == Some(moved_to) {
+                    self.selected = Some(index);
+                }
+            }
+            None => {}
+        }
+
+        // contents are borrowed mutably only for edits, which rebuild the scene's hierarchy
+        if let Some(index) = self
+            .selected
+            .filter(|index| *index < scene.contents().len())
+        {
+            let node = &scene.contents()[index];
+            ui.separator();
+            let mut name = node.name.clone();
+            let name_changed = ui
+                .horizontal(|ui| {
+                    ui.label("Name");
+                    ui.text_edit_singleline(&mut name).changed()
+                })
+                .inner;
+            // the copy stays valid while the node holds the object it was made from, anything
+            // else replacing the object (undo, animation) starts a new one
+            let (source, object) = match &mut self.editing {
+                Some((source, object)) if Arc::ptr_eq(source, &node.object) => (source, object),
+                editing => {
+                    let (source, object) =
+                        editing.insert((node.object.clone(), node.object.as_ref().clone()));
+                    (source, object)
+                }
+            };
+            let object_changed = Self::object_editor(ui, object);
+
+            if name_changed {
+                scene.contents_mut()[index].name = name;
+            }
+            if object_changed {
+                *source = Arc::new(object.clone());
+                scene.contents_mut()[index].object = source.clone();
+            }
+        }
+    }
+
+    fn volume_editor(ui: &mut Ui, volume: &mut VoxelVolume) -> bool {
+        let mut changed = false;
+        for (label, corner) in [("Min", &mut volume.min), ("Max", &mut volume.max)] {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                for coordinate in &mut corner.data {
+                    changed |= ui.add(DragValue::new(coordinate).speed(0.01)).changed();
+                }
+            });
+        }
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut volume.density, 0.1..=1000.0)
+                    .logarithmic(true)
+                    .text("Density"),
+            )
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut volume.anisotropy, -0.95..=0.95).text("Anisotropy"))
+            .changed();
+        for (label, color) in [
+            ("Color", &mut volume.color),
+            ("Emission", &mut volume.emission),
+        ] {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                let mut x = (*color).into();
+                changed |= ui.color_edit_button_rgb(&mut x).changed();
+                *color = x.into()
+            });
+        }
+
+        ui.label(format!("Voxels: {}", volume.grid.source()));
+        if let Some(error) = volume.grid.error() {
+            ui.label(format!("Unable to load the voxels: {}", error));
+        }
+        let mut source = volume.grid.source().clone();
+        let source_changed = match &mut source {
+            VoxelSource::Noise {
+                resolution,
+                frequency,
+                octaves,
+                seed,
+            } => {
+                ui.add(egui::Slider::new(resolution, 8..=128).text("Resolution"))
+                    .changed() |
+                    ui.add(egui::Slider::new(frequency, 0.5..=16.0).text("Frequency"))
+                        .changed() |
+                    ui.add(egui::Slider::new(octaves, 1..=8).text("Octaves"))
+                        .changed() |
+                    ui.add(DragValue::new(seed).prefix("Seed ")).changed()
+            }
+            VoxelSource::RawFile { .. } => {
+                let clicked = ui.button("Replace with noise").clicked();
+                if clicked {
+                    source = VoxelSource::default();
+                }
+                clicked
+            }
+        };
+        if source_changed {
+            volume.grid = VoxelGrid::new(source).expect("Noise grid can't fail to generate");
+            changed = true;
+        }
+
+        // the file to load is kept in egui memory until it's loaded successfully
+        ui.collapsing("Load raw file", |ui| {
+            let id = Id::new("raw voxel file");
+            let (mut path, mut size, mut status) = ui
+                .data()
+                .get_temp::<(String, [usize; 3], String)>(id)
+                .unwrap_or_else(|| ("volume.raw".to_string(), [64; 3], String::new()));
+            ui.horizontal(|ui| {
+                ui.label("Path");
+                ui.text_edit_singleline(&mut path);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Size");
+                for side in &mut size {
+                    ui.add(DragValue::new(side).clamp_range(2..=1024));
+                }
+            });
+            if ui.button("Load").clicked() {
+                let source = VoxelSource::RawFile {
+                    path: PathBuf::from(&path),
+                    size,
+                };
+                status = match VoxelGrid::new(source) {
+                    Ok(grid) => {
+                        volume.grid = grid;
+                        changed = true;
+                        "Loaded".to_string()
+                    }
+                    Err(error) => format!("Load failed: {}", error),
+                };
+            }
+            if !status.is_empty() {
+                ui.label(&status);
+            }
+            ui.data().insert_temp(id, (path, size, status));
+        });
+        changed
+    }
+
+    /// Editor of the object's properties, returns whether any of them changed
+    fn object_editor(ui: &mut Ui, object: &mut SceneObject) -> bool {
+        match object {
+            SceneObject::Sphere(sphere) => {
+                let mut changed = Self::center_editor(ui, &mut sphere.center);
+                let mut moving = sphere.end_center.is_some();
+                if ui.checkbox(&mut moving, "Moving").changed() {
+                    sphere.end_center = moving.then_some(sphere.center);
+                    changed = true;
+                }
+                if let Some(end_center) = &mut sphere.end_center {
+                    ui.horizontal(|ui| {
+                        ui.label("End center");
+                        for coordinate in &mut end_center.data {
+                            changed |= ui.add(DragValue::new(coordinate).speed(0.01)).changed();
+                        }
+                    });
+                }
+                changed |= ui
+                    .add(
+                        egui::Slider::new(&mut sphere.radius, 0.0..=sphere.max_radius)
+                            .text("Sphere radius"),
+                    )
+                    .changed();
+                changed | Self::color_editor(ui, &mut sphere.color)
+            }
+            SceneObject::Cylinder(cylinder) => {
+                Self::center_editor(ui, &mut cylinder.center) |
+                    ui.add(egui::Slider::new(&mut cylinder.radius, 0.01..=2.0).text("Radius"))
+                        .changed() |
+                    ui.add(egui::Slider::new(&mut cylinder.height, 0.01..=4.0).text("Height"))
+                        .changed() |
+                    Self::color_editor(ui, &mut cylinder.color)
+            }
+            SceneObject::Cone(cone) => {
+                Self::center_editor(ui, &mut cone.center) |
+                    ui.add(egui::Slider::new(&mut cone.radius, 0.01..=2.0).text("Base radius"))
+                        .changed() |
+                    ui.add(egui::Slider::new(&mut cone.height, 0.01..=4.0).text("Height"))
+                        .changed() |
+                    Self::color_editor(ui, &mut cone.color)
+            }
+            SceneObject::Disk(disk) => {
+                Self::center_editor(ui, &mut disk.center) |
+                    ui.add(egui::Slider::new(&mut disk.radius, 0.01..=10.0).text("Radius"))
+                        .changed() |
+                    Self::color_editor(ui, &mut disk.color)
+            }
+            SceneObject::Torus(torus) => {
+                Self::center_editor(ui, &mut torus.center) |
+                    ui.add(
+                        egui::Slider::new(&mut torus.major_radius, 0.01..=2.0).text("Major radius"),
+                    )
+                    .changed() |
+                    ui.add(
+                        egui::Slider::new(&mut torus.minor_radius, 0.01..=1.0).text("Minor radius"),
+                    )
+                    .changed() |
+                    Self::color_editor(ui, &mut torus.color)
+            }
+            SceneObject::Capsule(capsule) => {
+                Self::center_editor(ui, &mut capsule.center) |
+                    ui.add(egui::Slider::new(&mut capsule.radius, 0.01..=2.0).text("Radius"))
+                        .changed() |
+                    ui.add(egui::Slider::new(&mut capsule.height, 0.0..=4.0).text("Height"))
+                        .changed() |
+                    Self::color_editor(ui, &mut capsule.color)
+            }
+            SceneObject::ConstantMedium(medium) => {
+                ui.add(
+                    egui::Slider::new(&mut medium.density, 0.01..=100.0)
+                        .logarithmic(true)
+                        .text("Density"),
+                )
+                .changed() |
+                    Self::color_editor(ui, &mut medium.color) |
+                    ui.collapsing("Boundary", |ui| {
+                        Self::object_editor(ui, &mut medium.boundary)
+                    })
+                    .body_returned
+                    .unwrap_or(false)
+            }
+            SceneObject::VoxelVolume(volume) => Self::volume_editor(ui, volume),
+            SceneObject::Csg(csg) => {
+                egui::ComboBox::from_label("Operation")
+                    .selected_text(format!("{}", csg.operation))
+                    .show_ui(ui, |ui| {
+                        let mut changed = false;
+                        for operation in CsgOperation::ALL {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut csg.operation,
+                                    operation,
+                                    format!("{}", operation),
+                                )
+                                .changed();
+                        }
+                        changed
+                    })
+                    .inner
+                    .unwrap_or(false) |
+                    ui.collapsing("First object", |ui| Self::object_editor(ui, &mut csg.first))
+                        .body_returned
+                        .unwrap_or(false) |
+                    ui.collapsing("Second object", |ui| {
+                        Self::object_editor(ui, &mut csg.second)
+                    })
+                    .body_returned
+                    .unwrap_or(false)
+            }
+            SceneObject::Sdf(sdf) => {
+                Self::center_editor(ui, &mut sdf.center) |
+                    ui.add(
+                        egui::Slider::new(&mut sdf.bound_radius, 0.01..=10.0).text("Bounds radius"),
+                    )
+                    .changed() |
+                    Self::color_editor(ui, &mut sdf.color) |
+                    ui.collapsing(sdf.shape.kind_name(), |ui| {
+                        Self::sdf_shape_editor(ui, &mut sdf.shape)
+                    })
+                    .body_returned
+                    .unwrap_or(false)
+            }
+            SceneObject::Instanced(instanced) => Self::instances_editor(ui, instanced),
+            SceneObject::Transformed(transformed) => {
+                let mut changed = ui
+                    .collapsing("Start transform", |ui| {
+                        Self::transform_editor(ui, &mut transformed.start)
+                    })
+                    .body_returned
+                    .unwrap_or(false) |
+                    ui.collapsing("End transform", |ui| {
+                        Self::transform_editor(ui, &mut transformed.end)
+                    })
+                    .body_returned
+                    .unwrap_or(false);
+                if ui.button("Copy start to end").clicked() {
+                    transformed.end = transformed.start;
+                    changed = true;
+                }
+                changed |
+                    ui.collapsing("Object", |ui| {
+                        Self::object_editor(ui, &mut transformed.object)
+                    })
+                    .body_returned
+                    .unwrap_or(false)
+            }
+        }
+    }
+
+    fn instances_editor(ui: &mut Ui, instanced: &mut Instanced) -> bool {
+        ui.label(format!("{} instances", instanced.instances().len()));
+        let mut changed = false;
+        ui.collapsing("Shared geometry", |ui| {
+            for index in 0..instanced.geometry().objects().len() {
+                // edits replace the object, which rebuilds the hierarchies
+                let mut object = instanced.geometry().objects()[index].clone();
+                let object_changed = ui
+                    .push_id(index, |ui| {
+                        ui.collapsing(object.name(), |ui| Self::object_editor(ui, &mut object))
+                            .body_returned
+                            .unwrap_or(false)
+                    })
+                    .inner;
+                if object_changed {
+                    instanced.set_geometry_object(index, object);
+                    changed = true;
+                }
+            }
+        });
+        if instanced.instances().is_empty() {
+            return changed;
+        }
+
+        let id = Id::new("selected instance");
+        let mut selected = ui.data().get_temp::<usize>(id).unwrap_or(0);
+        selected = selected.min(instanced.instances().len() - 1);
+        ui.add(
+            DragValue::new(&mut selected)
+                .clamp_range(0..=instanced.instances().len() - 1)
+                .prefix("Instance "),
+        );
+        ui.data().insert_temp(id, selected);
+
+        let mut instance = instanced.instances()[selected];
+        let mut instance_changed = Self::transform_editor(ui, &mut instance.transform);
+        let mut override_color = instance.color.is_some();
+        if ui.checkbox(&mut override_color, "Override color").changed() {
+            instance.color = override_color.then(|| instanced.color());
+            instance_changed = true;
+        }
+        if let Some(color) = &mut instance.color {
+            instance_changed |= Self::color_editor(ui, color);
+        }
+        if instance_changed {
+            instanced.set_instance(selected, instance);
+        }
+        changed | instance_changed
+    }
+
+    fn sdf_shape_editor(ui: &mut Ui, shape: &mut SdfShape) -> bool {
+        let vector_editor = |ui: &mut Ui, label: &str, vector: &mut Vec3| {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                let mut changed = false;
+                for coordinate in &mut vector.data {
+                    changed |= ui.add(DragValue::new(coordinate).speed(0.01)).changed();
+                }
+                changed
+            })
+            .inner
+        };
+        let mut changed = match shape {
+            SdfShape::Sphere { radius } => ui
+                .add(egui::Slider::new(radius, 0.01..=2.0).text("Radius"))
+                .changed(),
+            SdfShape::Box { half_size } => vector_editor(ui, "Half size", half_size),
+            SdfShape::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                ui.add(egui::Slider::new(major_radius, 0.01..=2.0).text("Major radius"))
+                    .changed() |
+                    ui.add(egui::Slider::new(minor_radius, 0.01..=1.0).text("Minor radius"))
+                        .changed()
+            }
+            SdfShape::Translate { offset, .. } => vector_editor(ui, "Offset", offset),
+            SdfShape::SmoothUnion { smoothness, .. } => ui
+                .add(egui::Slider::new(smoothness, 0.0..=1.0).text("Smoothness"))
+                .changed(),
+            SdfShape::Repeat { period, .. } => vector_editor(ui, "Period", period),
+            SdfShape::Displace {
+                amplitude,
+                frequency,
+                ..
+            } => {
+                ui.add(egui::Slider::new(amplitude, 0.0..=0.2).text("Amplitude"))
+                    .changed() |
+                    ui.add(egui::Slider::new(frequency, 0.0..=100.0).text("Frequency"))
+                        .changed()
+            }
+        };
+        for (index, child) in shape.children_mut().into_iter().enumerate() {
+            changed |= ui
+                .push_id(index, |ui| {
+                    ui.collapsing(child.kind_name(), |ui| Self::sdf_shape_editor(ui, child))
+                        .body_returned
+                        .unwrap_or(false)
+                })
+                .inner;
+        }
+        changed
+    }
+
+    fn center_editor(ui: &mut Ui, center: &mut Point3) -> bool {
+        ui.horizontal(|ui| {
+            ui.label("Center");
+            let mut changed = false;
+            for coordinate in &mut center.data {
+                changed |= ui.add(DragValue::new(coordinate).speed(0.01)).changed();
+            }
+            changed
+        })
+        .inner
+    }
+
+    fn color_editor(ui: &mut Ui, color: &mut Color3) -> bool {
+        ui.horizontal(|ui| {
+            ui.label("Color");
+            let mut x = (*color).into();
+            let changed = ui.color_edit_button_rgb(&mut x).changed();
+            *color = x.into();
+            changed
+        })
+        .inner
+    }
+
+    fn transform_editor(ui: &mut Ui, transform: &mut Transform) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Translation");
+            for coordinate in &mut transform.translation.data {
+                changed |= ui.add(DragValue::new(coordinate).speed(0.01)).changed();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Rotation");
+            for angle in &mut transform.rotation.data {
+                changed |= ui.add(DragValue::new(angle).suffix("°")).changed();
+            }
+        });
+        changed |
+            ui.add(
+                DragValue::new(&mut transform.scale)
+                    .speed(0.01)
+                    .clamp_range(0.01..=100.0)
+                    .prefix("Scale "),
+            )
+            .changed()
+    }
+}
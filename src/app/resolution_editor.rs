@@ -0,0 +1,101 @@
+use crate::renderer::resolution::{AspectRatio, Resolution, MAX_SIZE, MIN_SIZE};
+use crate::renderer::RenderParams;
+use egui::{DragValue, Ui};
+
+///
+/// Output size controls: presets, free width/height entry with optional aspect lock and
+/// the render scale
+///
+pub struct ResolutionEditor {
+    lock_aspect: bool,
+    /// Exact ratio kept by the lock with the resolution it was last applied to, taking it
+    /// from the rounded resolution on every edit would make it drift
+    locked_ratio: Option<(Resolution, f64)>,
+}
+
+impl ResolutionEditor {
+    pub fn new() -> Self {
+        Self {
+            lock_aspect: true,
+            locked_ratio: None,
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, params: &mut RenderParams) {
+        egui::ComboBox::from_label("Resolution preset")
+            .selected_text(format!("{}", params.resolution))
+            .show_ui(ui, |ui| {
+                for res in &params.available_resolutions {
+                    ui.selectable_value(&mut params.resolution, *res, format!("{}", res));
+                }
+            });
+
+        let resolution = params.resolution;
+        // a resolution set elsewhere (preset, undo) brings its own ratio
+        let aspect_ratio = match self.locked_ratio {
+            Some((locked, ratio)) if locked == resolution => ratio,
+            _ => resolution.aspect_ratio(),
+        };
+        ui.horizontal(|ui| {
+            let mut width = resolution.width();
+            let mut height = resolution.height();
+            ui.label("Width");
+            let width_changed = ui
+                .add(DragValue::new(&mut width).clamp_range(MIN_SIZE..=MAX_SIZE))
+                .changed();
+            ui.label("Height");
+            let height_changed = ui
+                .add(DragValue::new(&mut height).clamp_range(MIN_SIZE..=MAX_SIZE))
+                .changed();
+
+            params.resolution = match (width_changed, height_changed, self.lock_aspect) {
+                (true, _, true) => Resolution::from_width(width, aspect_ratio),
+                (_, true, true) => Resolution::from_height(height, aspect_ratio),
+                (true, _, false) | (_, true, false) => Resolution::new(width, height),
+                _ => resolution,
+            };
+            if self.lock_aspect {
+                self.locked_ratio = Some((params.resolution, aspect_ratio));
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let current = AspectRatio::ALL
+                .into_iter()
+                .find(|aspect| (aspect.ratio() - aspect_ratio).abs() < 0.01);
+            egui::ComboBox::from_label("Aspect ratio")
+                .selected_text(match current {
+                    Some(aspect) => format!("{}", aspect),
+                    None => format!("{:.2}:1", aspect_ratio),
+                })
+                .show_ui(ui, |ui| {
+                    for aspect in AspectRatio::ALL {
+                        if ui
+                            .selectable_label(current == Some(aspect), format!("{}", aspect))
+                            .clicked()
+                        {
+                            params.resolution =
+                                Resolution::from_width(resolution.width(), aspect.ratio());
+                            self.locked_ratio = Some((params.resolution, aspect.ratio()));
+                        }
+                    }
+                });
+            ui.checkbox(&mut self.lock_aspect, "Lock");
+        });
+
+        let mut percent = params.render_scale * 100.0;
+        if ui
+            .add(
+                egui::Slider::new(&mut percent, 10.0..=200.0)
+                    .suffix("%")
+                    .text("Render scale"),
+            )
+            .changed()
+        {
+            params.render_scale = percent / 100.0;
+        }
+        if params.render_scale != 1.0 {
+            ui.label(format!("Rendering at {}", params.render_resolution()));
+        }
+    }
+}
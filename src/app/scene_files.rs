@@ -0,0 +1,111 @@
+use crate::renderer::scene::Scene;
+use crate::renderer::RenderParams;
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+const MAX_RECENT_FILES: usize = 8;
+
+///
+/// Contents of a saved scene file (RON), the scene together with its camera and render
+/// parameters
+///
+#[derive(Serialize, Deserialize)]
+pub struct SceneFile {
+    pub scene: Scene,
+    pub params: RenderParams,
+}
+
+impl SceneFile {
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        std::fs::write(path, text)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        ron::from_str(&text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+///
+/// Open/save controls for scene files with the list of recently used files
+///
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SceneFiles {
+    path: String,
+    pub(crate) recent: Vec<PathBuf>,
+    #[serde(skip)]
+    status: Option<String>,
+}
+
+impl SceneFiles {
+    pub fn new() -> Self {
+        Self {
+            path: "scene.ron".to_string(),
+            recent: vec![],
+            status: None,
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, scene: &mut Scene, params: &mut RenderParams) {
+        ui.horizontal(|ui| {
+            ui.label("Scene file");
+            ui.text_edit_singleline(&mut self.path);
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Open").clicked() {
+                self.open(PathBuf::from(&self.path), scene, params);
+            }
+            if ui.button("Save").clicked() {
+                let path = PathBuf::from(&self.path);
+                let file = SceneFile {
+                    scene: scene.clone(),
+                    params: params.clone(),
+                };
+                self.status = Some(match file.save(&path) {
+                    Ok(()) => {
+                        self.remember(path);
+                        "Saved".to_string()
+                    }
+                    Err(error) => format!("Save failed: {}", error),
+                });
+            }
+            ui.add_enabled_ui(!self.recent.is_empty(), |ui| {
+                ui.menu_button("Recent", |ui| {
+                    for path in self.recent.clone() {
+                        if ui.button(path.display().to_string()).clicked() {
+                            self.open(path, scene, params);
+                            ui.close_menu();
+                        }
+                    }
+                });
+            });
+            if let Some(status) = &self.status {
+                ui.label(status);
+            }
+        });
+    }
+
+    fn open(&mut self, path: PathBuf, scene: &mut Scene, params: &mut RenderParams) {
+        self.status = Some(match SceneFile::load(&path) {
+            Ok(file) => {
+                *scene = file.scene;
+                *params = file.params;
+                self.path = path.display().to_string();
+                self.remember(path);
+                "Opened".to_string()
+            }
+            Err(error) => format!("Open failed: {}", error),
+        });
+    }
+
+    /// Move the path to the top of the recent files
+    fn remember(&mut self, path: PathBuf) {
+        self.recent.retain(|recent| *recent != path);
+        self.recent.insert(0, path);
+        self.recent.truncate(MAX_RECENT_FILES);
+    }
+}
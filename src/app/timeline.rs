@@ -0,0 +1,284 @@
+use crate::renderer::animation::Interpolation;
+use crate::renderer::camera::CameraView;
+use crate::renderer::scene::Scene;
+use egui::{pos2, vec2, Align2, Color32, DragValue, FontId, Sense, Shape, Stroke, Ui};
+use std::time::{Duration, Instant};
+
+const CAMERA_KEY_COLOR: Color32 = Color32::from_rgb(240, 200, 60);
+const OBJECT_KEY_COLOR: Color32 = Color32::from_rgb(90, 200, 240);
+const CURRENT_FRAME_COLOR: Color32 = Color32::from_rgb(230, 70, 70);
+/// Smallest distance between labelled frame ticks, in points
+const MIN_TICK_SPACING: f32 = 40.0;
+
+pub enum TimelineAction {
+    /// The scene and camera were posed for another frame
+    FrameChanged,
+    RenderSequence(String),
+    CancelSequence,
+}
+
+///
+/// Frame range, playback and keyframing controls of the scene animation
+///
+pub struct Timeline {
+    playing: bool,
+    last_step: Instant,
+    /// Interpolation of newly added keys
+    interpolation: Interpolation,
+    output: String,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self {
+            playing: false,
+            last_step: Instant::now(),
+            interpolation: Interpolation::Bezier,
+            output: "frames/frame_####.png".to_string(),
+        }
+    }
+
+    pub fn playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        scene: &mut Scene,
+        camera: &mut CameraView,
+        selected: Option<usize>,
+        rendering_sequence: bool,
+    ) -> Option<TimelineAction> {
+        if rendering_sequence {
+            let mut action = None;
+            ui.horizontal(|ui| {
+                ui.label(format!("Rendering frames to {}", self.output));
+                if ui.button("Cancel sequence").clicked() {
+                    action = Some(TimelineAction::CancelSequence);
+                }
+            });
+            return action;
+        }
+
+        let mut action = None;
+        let animation = &mut scene.animation;
+        let mut frame = animation.current;
+
+        ui.horizontal(|ui| {
+            if ui.button("⏮").on_hover_text("First frame").clicked() {
+                frame = animation.start;
+            }
+            if ui.button("◀").on_hover_text("Previous frame").clicked() {
+                frame = frame.saturating_sub(1).max(animation.start);
+            }
+            let play = match self.playing {
+                true => "⏸",
+                false => "▶",
+            };
+            if ui.button(play).on_hover_text("Play/pause").clicked() {
+                self.playing = !self.playing;
+                self.last_step = Instant::now();
+            }
+            if ui.button("▶|").on_hover_text("Next frame").clicked() {
+                frame = (frame + 1).min(animation.end);
+            }
+            if ui.button("⏭").on_hover_text("Last frame").clicked() {
+                frame = animation.end;
+            }
+            ui.add(DragValue::new(&mut frame).prefix("Frame "));
+            ui.separator();
+            ui.add(DragValue::new(&mut animation.start).prefix("Start "));
+            ui.add(
+                DragValue::new(&mut animation.end)
+                    .prefix("End ")
+                    .clamp_range(animation.start..=u32::MAX),
+            );
+            ui.add(
+                DragValue::new(&mut animation.fps)
+                    .prefix("FPS ")
+                    .clamp_range(1.0..=120.0),
+            );
+        });
+        frame = frame.clamp(animation.start, animation.end);
+
+        if self.playing {
+            let frame_time = Duration::from_secs_f64(1.0 / animation.fps.max(1.0));
+            if self.last_step.elapsed() >= frame_time {
+                self.last_step = Instant::now();
+                frame = match frame >= animation.end {
+                    true => animation.start,
+                    false => frame + 1,
+                };
+            }
+        }
+
+        let node = selected.and_then(|index| scene.contents().get(index));
+        let object_keys: Vec<u32> = node
+            .map(|node| {
+                let tracks = &node.tracks;
+                let position = tracks.position.keys().iter().map(|key| key.frame);
+                let color = tracks.color.keys().iter().map(|key| key.frame);
+                position.chain(color).collect()
+            })
+            .unwrap_or_default();
+        let camera_keys: Vec<u32> = scene
+            .animation
+            .camera
+            .keys()
+            .iter()
+            .map(|key| key.frame)
+            .collect();
+        if let Some(clicked) = Self::strip(
+            ui,
+            [scene.animation.start, scene.animation.end],
+            frame,
+            &camera_keys,
+            &object_keys,
+        ) {
+            frame = clicked;
+        }
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("New keys")
+                .selected_text(format!("{}", self.interpolation))
+                .show_ui(ui, |ui| {
+                    for interpolation in Interpolation::ALL {
+                        ui.selectable_value(
+                            &mut self.interpolation,
+                            interpolation,
+                            format!("{}", interpolation),
+                        );
+                    }
+                });
+            let animation = &mut scene.animation;
+            if ui.button("Key camera").clicked() {
+                animation.camera.set_key(frame, *camera, self.interpolation);
+            }
+            if ui
+                .add_enabled(
+                    animation.camera.has_key(frame),
+                    egui::Button::new("Delete camera key"),
+                )
+                .clicked()
+            {
+                animation.camera.remove_key(frame);
+            }
+            ui.separator();
+            match selected.filter(|index| *index < scene.contents().len()) {
+                Some(index) => {
+                    let node = &scene.contents()[index];
+                    let position = node.object.position();
+                    let color = node.object.color();
+                    let tracks = scene.tracks_mut(index).expect("Selected object exists");
+                    if ui.button("Key position").clicked() {
+                        tracks.position.set_key(frame, position, self.interpolation);
+                    }
+                    if ui.button("Key color").clicked() {
+                        tracks.color.set_key(frame, color, self.interpolation);
+                    }
+                    let has_keys = tracks.position.has_key(frame) || tracks.color.has_key(frame);
+                    if ui
+                        .add_enabled(has_keys, egui::Button::new("Delete object keys"))
+                        .clicked()
+                    {
+                        tracks.position.remove_key(frame);
+                        tracks.color.remove_key(frame);
+                    }
+                }
+                None => {
+                    ui.label("Select an object to key its position and color");
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Output");
+            ui.text_edit_singleline(&mut self.output)
+                .on_hover_text("# characters are replaced by the frame number");
+            if ui.button("Render sequence").clicked() {
+                self.playing = false;
+                action = Some(TimelineAction::RenderSequence(self.output.clone()));
+            }
+        });
+
+        if frame != scene.animation.current {
+            scene.set_frame(frame, camera);
+            action = action.or(Some(TimelineAction::FrameChanged));
+        }
+        action
+    }
+
+    ///
+    /// Frame ruler with key markers, returns the frame clicked or dragged to
+    ///
+    fn strip(
+        ui: &mut Ui,
+        range: [u32; 2],
+        current: u32,
+        camera_keys: &[u32],
+        object_keys: &[u32],
+    ) -> Option<u32> {
+        let (rect, response) =
+            ui.allocate_exact_size(vec2(ui.available_width(), 36.0), Sense::click_and_drag());
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+        let frames = (range[1] - range[0]).max(1) as f32;
+        let x = |frame: u32| rect.left() + (frame - range[0]) as f32 / frames * rect.width();
+        let frame_at = |x: f32| {
+            let t = ((x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+            range[0] + (t * frames).round() as u32
+        };
+
+        let step = (MIN_TICK_SPACING / rect.width() * frames).ceil().max(1.0) as u32;
+        let tick_stroke = Stroke::new(1.0, visuals.weak_text_color());
+        for frame in (range[0]..=range[1]).step_by(step as usize) {
+            painter.vline(x(frame), rect.top()..=rect.top() + 6.0, tick_stroke);
+            painter.text(
+                pos2(x(frame) + 2.0, rect.top() + 4.0),
+                Align2::LEFT_TOP,
+                frame.to_string(),
+                FontId::monospace(10.0),
+                visuals.weak_text_color(),
+            );
+        }
+
+        let diamond = |frame: u32, y: f32, color: Color32| {
+            let center = pos2(x(frame), y);
+            Shape::convex_polygon(
+                vec![
+                    center + vec2(0.0, -4.0),
+                    center + vec2(4.0, 0.0),
+                    center + vec2(0.0, 4.0),
+                    center + vec2(-4.0, 0.0),
+                ],
+                color,
+                Stroke::none(),
+            )
+        };
+        for &frame in camera_keys
+            .iter()
+            .filter(|frame| (range[0]..=range[1]).contains(frame))
+        {
+            painter.add(diamond(frame, rect.bottom() - 14.0, CAMERA_KEY_COLOR));
+        }
+        for &frame in object_keys
+            .iter()
+            .filter(|frame| (range[0]..=range[1]).contains(frame))
+        {
+            painter.add(diamond(frame, rect.bottom() - 6.0, OBJECT_KEY_COLOR));
+        }
+        painter.vline(
+            x(current.clamp(range[0], range[1])),
+            rect.y_range(),
+            Stroke::new(2.0, CURRENT_FRAME_COLOR),
+        );
+
+        response
+            .interact_pointer_pos()
+            .filter(|_| response.clicked() || response.dragged())
+            .map(|pointer| frame_at(pointer.x))
+    }
+}
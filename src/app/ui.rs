@@ -1,41 +1,235 @@
+use crate::app::history::{HistoryAction, RenderHistory};
+use crate::app::image_view::{clamped_uv, image_uv, uv_to_pixel, ImageInteraction, ImageView};
+use crate::app::navigation::navigate;
+use crate::app::timeline::TimelineAction;
+use crate::app::SavedState;
+use crate::renderer::animation::save_frame;
+use crate::renderer::camera::{Camera, CameraView, Projection};
+use crate::renderer::export::export_passes;
+use crate::renderer::filter::FilterKind;
+use crate::renderer::frame::Frame;
 use crate::renderer::hittable::Hittable;
-use crate::renderer::scene::{Scene, SceneObject};
+use crate::renderer::pass::{heat_color, RenderPass};
+use crate::renderer::region::Region;
+use crate::renderer::sampler::SamplerKind;
+use crate::renderer::scene::Scene;
+use crate::renderer::stats::RenderStats;
 use crate::renderer::{RenderParams, Renderer};
-use crate::{MyApp, Vec3};
-use egui::{Button, Color32, ColorImage, ProgressBar, Response, Stroke, TextureFilter, TextureHandle, Ui, Widget};
+use crate::MyApp;
+use egui::{
+    pos2, vec2, Align2, Button, Color32, ColorImage, FontId, Pos2, ProgressBar, Rect, Sense, Shape,
+    Stroke, TextureFilter, TextureHandle, Ui, Widget,
+};
+use std::time::{Duration, Instant};
 
 pub struct RenderBox {
     tex_handle: Option<TextureHandle>,
+    heatmap_handle: Option<TextureHandle>,
+    selection_handle: Option<TextureHandle>,
+    /// Object the selection overlay was generated for
+    highlighted: Option<usize>,
     render_image: ColorImage,
+    frame: Option<Frame>,
+    pass: RenderPass,
+    show_sample_heatmap: bool,
+    export_prefix: String,
+    export_status: Option<String>,
     renderer: Renderer,
-    scene: Scene,
+    pub(crate) scene: Scene,
+    /// Time of the last camera movement not yet followed by a full quality render
+    navigated_at: Option<Instant>,
+    image_view: ImageView,
+    interaction: ImageInteraction,
+    texture_filter: TextureFilter,
+    history: RenderHistory,
+    /// Parameters and scene of the requested full render, recorded in history when done
+    pending: Option<(RenderParams, Scene)>,
+    /// Corner where the region selection drag started
+    region_start: Option<Pos2>,
+    /// Statistics of the last finished full quality render
+    pub(crate) last_stats: Option<RenderStats>,
+    sequence: Option<Sequence>,
+    pub(crate) sequence_status: Option<String>,
 }
 
+///
+/// Animation frames being rendered one after another into numbered images
+///
+struct Sequence {
+    frame: u32,
+    end: u32,
+    pattern: String,
+}
+
+/// Pause in camera movement after which the full quality render starts
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+const ZOOM_SPEED: f32 = 0.002;
+
 impl RenderBox {
     pub fn new() -> RenderBox {
         let image_data = ColorImage::new([800, 600], Color32::default());
         Self {
             tex_handle: None,
+            heatmap_handle: None,
+            selection_handle: None,
+            highlighted: None,
             render_image: image_data,
+            frame: None,
+            pass: RenderPass::Beauty,
+            show_sample_heatmap: false,
+            export_prefix: "render".to_string(),
+            export_status: None,
             renderer: Renderer::create(),
             scene: Scene::default(),
+            navigated_at: None,
+            image_view: ImageView::new(),
+            interaction: ImageInteraction::Camera,
+            texture_filter: TextureFilter::Linear,
+            history: RenderHistory::new(),
+            pending: None,
+            region_start: None,
+            last_stats: None,
+            sequence: None,
+            sequence_status: None,
         }
     }
     pub fn request_render(&mut self, params: RenderParams) {
-        self.renderer.request_render(params, &self.scene);
+        self.cancel_sequence();
+        self.renderer.request_render(params.clone(), &self.scene);
+        self.pending = Some((params, self.scene.clone()));
+    }
+
+    ///
+    /// Quick low quality render, the full one follows when nothing changes for a while. The
+    /// region is cleared as it no longer matches the rest of the image.
+    ///
+    pub fn request_preview(&mut self, params: &mut RenderParams) {
+        params.region = None;
+        self.cancel_sequence();
+        self.renderer.request_render(params.preview(), &self.scene);
+        self.pending = None;
+        self.navigated_at = Some(Instant::now());
+    }
+
+    ///
+    /// Render the scene's frame range into images named by `pattern`, see [`save_frame`]
+    ///
+    pub fn render_sequence(&mut self, params: &mut RenderParams, pattern: String) {
+        params.region = None;
+        self.sequence = Some(Sequence {
+            frame: self.scene.animation.start,
+            end: self.scene.animation.end,
+            pattern,
+        });
+        self.render_sequence_frame(params);
+    }
+
+    pub fn rendering_sequence(&self) -> bool {
+        self.sequence.is_some()
+    }
+
+    pub fn cancel_sequence(&mut self) {
+        if let Some(sequence) = self.sequence.take() {
+            self.sequence_status = Some(format!("Cancelled at frame {}", sequence.frame));
+        }
+    }
+
+    fn render_sequence_frame(&mut self, params: &mut RenderParams) {
+        if let Some(sequence) = &self.sequence {
+            self.scene.set_frame(sequence.frame, &mut params.camera);
+            self.renderer.request_render(params.clone(), &self.scene);
+            self.pending = None;
+            self.navigated_at = None;
+            self.sequence_status = Some(format!(
+                "Rendering frame {} of {}",
+                sequence.frame, sequence.end
+            ));
+        }
+    }
+
+    /// Save the finished frame of the sequence and start the next one
+    fn sequence_frame_done(&mut self, frame: &Frame, params: &mut RenderParams) {
+        let sequence = match &mut self.sequence {
+            Some(sequence) => sequence,
+            None => return,
+        };
+        let path = match save_frame(&frame.image, &sequence.pattern, sequence.frame) {
+            Ok(path) => path,
+            Err(error) => {
+                self.sequence_status =
+                    Some(format!("Saving frame {} failed: {}", sequence.frame, error));
+                self.sequence = None;
+                return;
+            }
+        };
+        sequence.frame += 1;
+        if sequence.frame > sequence.end {
+            self.sequence_status = Some(format!("Sequence saved, last frame {}", path.display()));
+            self.sequence = None;
+        } else {
+            self.render_sequence_frame(params);
+        }
+    }
+
+    ///
+    /// Index of the object visible at `uv` of the image (from top left, in [0, 1]²)
+    ///
+    fn pick(scene: &Scene, params: &RenderParams, uv: Pos2) -> Option<usize> {
+        let size: [usize; 2] = params.render_resolution().into();
+        let camera = Camera::new(
+            size,
+            &params.camera,
+            params.projection,
+            params.focal_length,
+            0.0,
+            params.focus_distance,
+        );
+        let u = uv.x as f64 * camera.viewport_width;
+        let v = (1.0 - uv.y as f64) * camera.viewport_height;
+        let ray = camera.cast_ray(u, v, [0.5, 0.5], params.shutter_open);
+        let mut sampler = params.sampler.create(1);
+        sampler.start_sample([0, 0], 0);
+        scene
+            .hit(
+                &ray,
+                params.min_ray_distance,
+                f64::INFINITY,
+                sampler.as_mut(),
+            )
+            .map(|hit| hit.object)
     }
 
-    pub fn draw(&mut self, ui: &mut Ui) {
+    pub fn draw(&mut self, ui: &mut Ui, params: &mut RenderParams, selected: &mut Option<usize>) {
+        let mut image_changed = false;
+        if let Some(frame) = self.renderer.update_result() {
+            // region renders are pasted over the image they were started from
+            let frame = match self.frame.take() {
+                Some(mut base) if frame.region.is_some() && base.size() == frame.size() => {
+                    base.composite(&frame);
+                    base
+                }
+                _ => frame,
+            };
+            self.heatmap_handle = None;
+            self.selection_handle = None;
+            self.history.shown = None;
+            if !self.renderer.waiting_for_next_frame {
+                self.sequence_frame_done(&frame, params);
+                if let Some((params, scene)) = self.pending.take() {
+                    self.last_stats = Some(frame.stats);
+                    self.history.push(ui, &frame, params, scene);
+                }
+            }
+            self.frame = Some(frame);
+            image_changed = true;
+        }
+
         let texture: &mut TextureHandle = self.tex_handle.get_or_insert_with(|| {
             // Load the texture only once.
             ui.ctx()
                 .load_texture("my-image", self.render_image.clone(), TextureFilter::Linear)
         });
 
-        self.renderer
-            .update_result(&mut self.render_image);
-
-        texture.set(self.render_image.clone(), TextureFilter::Linear);
         ui.vertical(|ui| {
             if self.renderer.waiting_for_next_frame {
                 let pb = ProgressBar::new(self.renderer.progress as f32)
@@ -43,27 +237,441 @@ impl RenderBox {
                     .show_percentage();
                 pb.ui(ui);
             }
-            ui.image(texture, ui.available_size());
+            match self.history.show_strip(ui) {
+                Some(HistoryAction::Show(number)) => {
+                    if let Some(entry) = self.history.get(number) {
+                        self.frame = Some(entry.frame.clone());
+                        self.history.shown = Some(number);
+                        self.heatmap_handle = None;
+                        self.selection_handle = None;
+                        image_changed = true;
+                    }
+                }
+                Some(HistoryAction::Restore(number)) => {
+                    if let Some(entry) = self.history.get(number) {
+                        *params = entry.params.clone();
+                        self.scene = entry.scene.clone();
+                        *selected = None;
+                    }
+                }
+                None => {}
+            }
+            if let Some(frame) = &self.frame {
+                ui.horizontal(|ui| {
+                    let previous_pass = self.pass;
+                    egui::ComboBox::from_label("Pass")
+                        .selected_text(format!("{}", self.pass))
+                        .show_ui(ui, |ui| {
+                            for pass in RenderPass::ALL {
+                                ui.selectable_value(&mut self.pass, pass, format!("{}", pass));
+                            }
+                        });
+                    image_changed |= previous_pass != self.pass;
+
+                    ui.checkbox(&mut self.show_sample_heatmap, "Sample count heatmap");
+                    if self.show_sample_heatmap {
+                        ui.label(format!("0 - {} samples", frame.max_sample_count()));
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Export prefix");
+                    ui.text_edit_singleline(&mut self.export_prefix);
+                    if ui.button("Export passes").clicked() {
+                        self.export_status =
+                            Some(match export_passes(frame, &self.export_prefix) {
+                                Ok(files) => format!("Exported {} images", files.len()),
+                                Err(error) => format!("Export failed: {}", error),
+                            });
+                    }
+                    if let Some(status) = &self.export_status {
+                        ui.label(status);
+                    }
+                });
+
+                if image_changed {
+                    self.render_image = self.pass.image(frame);
+                    texture.set(self.render_image.clone(), self.texture_filter);
+                }
+            }
+
+            let pixels_per_point = ui.ctx().pixels_per_point();
+            let image_size = texture.size_vec2();
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.interaction, ImageInteraction::Camera, "🎥 Camera")
+                    .on_hover_text(
+                        "Drag to orbit, shift/right drag to pan, ctrl drag to look around, \
+                         scroll to dolly, WASD/QE to fly",
+                    );
+                ui.selectable_value(
+                    &mut self.interaction,
+                    ImageInteraction::Inspect,
+                    "🔍 Inspect",
+                )
+                .on_hover_text("Scroll to zoom, drag to pan the image");
+                ui.selectable_value(&mut self.interaction, ImageInteraction::Region, "⬚ Region")
+                    .on_hover_text("Drag a rectangle to render only that part of the image");
+                ui.separator();
+                if ui.button("Fit").clicked() {
+                    self.image_view.fit();
+                }
+                if ui.button("1:1").clicked() {
+                    self.image_view.actual_size(pixels_per_point, image_size);
+                }
+                if params.region.is_some() {
+                    ui.separator();
+                    if ui.button("✖ Clear region").clicked() {
+                        params.region = None;
+                    }
+                }
+            });
+
+            let (pane, response) =
+                ui.allocate_exact_size(ui.available_size(), Sense::click_and_drag());
+            let image_rect = self.image_view.image_rect(pane, image_size);
+            match self.interaction {
+                ImageInteraction::Camera => {
+                    let pan_scale =
+                        2.0 / (params.focal_length.max(0.01) * image_rect.height() as f64);
+                    // the camera is animated while a sequence renders
+                    if self.sequence.is_none() &&
+                        navigate(ui, &response, &mut params.camera, pan_scale)
+                    {
+                        params.region = None;
+                        self.renderer.request_render(params.preview(), &self.scene);
+                        self.pending = None;
+                        self.navigated_at = Some(Instant::now());
+                    }
+                }
+                ImageInteraction::Inspect => {
+                    if let Some(pointer) = response.hover_pos() {
+                        let scroll = ui.input().scroll_delta.y * ZOOM_SPEED;
+                        let factor = scroll.exp() * ui.input().zoom_delta();
+                        if factor != 1.0 {
+                            self.image_view.zoom_at(factor, pointer, pane, image_size);
+                        }
+                    }
+                    if response.dragged() {
+                        self.image_view.pan(response.drag_delta(), pane, image_size);
+                    }
+                }
+                ImageInteraction::Region => {
+                    if let Some(pointer) = response.interact_pointer_pos() {
+                        let uv = clamped_uv(image_rect, pointer);
+                        if response.drag_started() {
+                            self.region_start = Some(uv);
+                        }
+                        if let (true, Some(start)) = (response.dragged(), self.region_start) {
+                            params.region = Some(Region::new(
+                                [start.x as f64, start.y as f64],
+                                [uv.x as f64, uv.y as f64],
+                            ));
+                        }
+                    }
+                    if response.drag_released() {
+                        self.region_start = None;
+                    }
+                }
+            }
+            if let Some(navigated_at) = self.navigated_at {
+                if navigated_at.elapsed() > SETTLE_TIME && !response.dragged() {
+                    self.navigated_at = None;
+                    self.renderer.request_render(params.clone(), &self.scene);
+                    self.pending = Some((params.clone(), self.scene.clone()));
+                }
+            }
+
+            let filter = self.image_view.filter(pane, image_size, pixels_per_point);
+            if filter != self.texture_filter {
+                self.texture_filter = filter;
+                texture.set(self.render_image.clone(), filter);
+            }
+
+            // the image can be zoomed past the pane, keep all drawing inside it
+            let image_rect = self.image_view.image_rect(pane, image_size);
+            let painter = ui.painter_at(pane);
+            let full_uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+            painter.add(Shape::image(
+                texture.id(),
+                image_rect,
+                full_uv,
+                Color32::WHITE,
+            ));
+
+            if response.clicked() {
+                if let Some(uv) = response
+                    .interact_pointer_pos()
+                    .and_then(|position| image_uv(image_rect, position))
+                {
+                    *selected = Self::pick(&self.scene, params, uv);
+                }
+            }
+
+            if self.highlighted != *selected {
+                self.highlighted = *selected;
+                self.selection_handle = None;
+            }
+            if let Some(comparison) = self.history.comparison_texture(ui, self.pass) {
+                // reference render on the left of the split line, shown image on the right
+                let split = image_rect.left() + image_rect.width() * self.history.split;
+                let left = Rect::from_min_max(image_rect.min, pos2(split, image_rect.max.y));
+                painter
+                    .with_clip_rect(left.intersect(pane))
+                    .add(Shape::image(
+                        comparison,
+                        image_rect,
+                        full_uv,
+                        Color32::WHITE,
+                    ));
+                painter.vline(split, pane.y_range(), Stroke::new(1.5, Color32::WHITE));
+                let font = FontId::proportional(14.0);
+                if let Some(number) = self.history.compare_with {
+                    painter.text(
+                        pos2(split - 6.0, pane.top() + 4.0),
+                        Align2::RIGHT_TOP,
+                        format!("#{}", number),
+                        font.clone(),
+                        Color32::WHITE,
+                    );
+                }
+                let shown = match self.history.shown {
+                    Some(number) => format!("#{}", number),
+                    None => "current".to_string(),
+                };
+                painter.text(
+                    pos2(split + 6.0, pane.top() + 4.0),
+                    Align2::LEFT_TOP,
+                    shown,
+                    font,
+                    Color32::WHITE,
+                );
+            }
+            if let (Some(object), Some(frame)) = (*selected, &self.frame) {
+                let overlay = self.selection_handle.get_or_insert_with(|| {
+                    ui.ctx().load_texture(
+                        "selection",
+                        selection_overlay(frame, object),
+                        TextureFilter::Nearest,
+                    )
+                });
+                painter.add(Shape::image(
+                    overlay.id(),
+                    image_rect,
+                    full_uv,
+                    Color32::WHITE,
+                ));
+            }
+            if let (true, Some(frame)) = (self.show_sample_heatmap, &self.frame) {
+                let heatmap = self.heatmap_handle.get_or_insert_with(|| {
+                    ui.ctx().load_texture(
+                        "sample-heatmap",
+                        sample_heatmap(frame),
+                        TextureFilter::Nearest,
+                    )
+                });
+                painter.add(Shape::image(
+                    heatmap.id(),
+                    image_rect,
+                    full_uv,
+                    Color32::from_white_alpha(200),
+                ));
+            }
+
+            if let Some(region) = params.region {
+                let corner = |uv: [f64; 2]| {
+                    image_rect.min + vec2(uv[0] as f32, uv[1] as f32) * image_rect.size()
+                };
+                painter.rect_stroke(
+                    Rect::from_min_max(corner(region.min), corner(region.max)),
+                    0.0,
+                    Stroke::new(1.5, REGION_COLOR),
+                );
+            }
+
+            let zoom = self.image_view.zoom(pane, image_size) * pixels_per_point;
+            let mut status = format!("{:.0}%", zoom * 100.0);
+            if let (Some(uv), Some(frame)) = (
+                response
+                    .hover_pos()
+                    .and_then(|position| image_uv(image_rect, position)),
+                &self.frame,
+            ) {
+                let [x, y] = uv_to_pixel(uv, frame.size());
+                let index = y * frame.size()[0] + x;
+                let [r, g, b] = frame.color[index].data;
+                status += &format!(
+                    "  pixel {}, {}  linear RGB {:.4} {:.4} {:.4}  {} samples",
+                    x, y, r, g, b, frame.sample_counts[index]
+                );
+            }
+            let galley = painter.layout_no_wrap(status, FontId::monospace(12.0), Color32::WHITE);
+            let status_rect = Rect::from_min_size(
+                pane.left_bottom() - vec2(0.0, galley.size().y + 4.0),
+                galley.size() + vec2(8.0, 4.0),
+            );
+            painter.rect_filled(status_rect, 0.0, Color32::from_black_alpha(160));
+            painter.galley(status_rect.min + vec2(4.0, 2.0), galley);
         });
     }
 }
 
+///
+/// Transparent image tinting the selected object and drawing its outline
+///
+fn selection_overlay(frame: &Frame, object: usize) -> ColorImage {
+    let [width, height] = frame.size();
+    let selected = |x: usize, y: usize| frame.object_id[y * width + x] == Some(object);
+    let mut pixels = vec![Color32::TRANSPARENT; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            if !selected(x, y) {
+                continue;
+            }
+            let edge = x == 0 ||
+                y == 0 ||
+                x == width - 1 ||
+                y == height - 1 ||
+                !selected(x - 1, y) ||
+                !selected(x + 1, y) ||
+                !selected(x, y - 1) ||
+                !selected(x, y + 1);
+            pixels[y * width + x] = match edge {
+                true => SELECTION_COLOR,
+                false => Color32::from_rgba_unmultiplied(255, 160, 40, 40),
+            };
+        }
+    }
+    ColorImage {
+        size: frame.size(),
+        pixels,
+    }
+}
+
+const SELECTION_COLOR: Color32 = Color32::from_rgb(255, 160, 40);
+const REGION_COLOR: Color32 = Color32::from_rgb(240, 240, 80);
+
+///
+/// Image with per-pixel sample counts mapped from blue (few samples) to red (maximum)
+///
+fn sample_heatmap(frame: &Frame) -> ColorImage {
+    let max = frame.max_sample_count().max(1) as f32;
+    let pixels = frame
+        .sample_counts
+        .iter()
+        .map(|count| heat_color(*count as f32 / max))
+        .collect();
+    ColorImage {
+        size: frame.size(),
+        pixels,
+    }
+}
+
+fn stats_grid(ui: &mut Ui, stats: &RenderStats) {
+    let rows = [
+        (
+            "Wall time",
+            format!("{:.3} s", stats.wall_time.as_secs_f64()),
+        ),
+        ("Primary rays", format!("{}", stats.primary_rays)),
+        ("Secondary rays", format!("{}", stats.secondary_rays)),
+        ("Shadow rays", format!("{}", stats.shadow_rays)),
+        (
+            "Rays per second",
+            format!("{:.2} M", stats.rays_per_second() / 1e6),
+        ),
+        (
+            "Average path length",
+            format!("{:.2}", stats.average_path_length()),
+        ),
+        (
+            "Intersection tests per ray",
+            format!("{:.2}", stats.tests_per_ray()),
+        ),
+        ("Scene memory", format!("{} B", stats.scene_memory)),
+    ];
+    egui::Grid::new("render-stats").show(ui, |ui| {
+        for (name, value) in rows {
+            ui.label(name);
+            ui.label(value);
+            ui.end_row();
+        }
+    });
+}
+
 impl eframe::App for MyApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        let state = SavedState {
+            scene: self.render_box.scene.clone(),
+            params: self.params.clone(),
+            scene_files: self.scene_files.clone(),
+        };
+        eframe::set_value(storage, eframe::APP_KEY, &state);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         egui::SidePanel::right("right_panel")
             .min_width(400.0)
             .show(ctx, |ui| {
-                ui.heading("Render parameters ");
+                self.scene_files
+                    .show(ui, &mut self.render_box.scene, &mut self.params);
+                if ui
+                    .button("Reset to defaults")
+                    .on_hover_text("Default scene, parameters and layout")
+                    .clicked()
+                {
+                    self.render_box.scene = Scene::default();
+                    self.params = RenderParams::default();
+                    self.outliner.selected = None;
+                    *ctx.memory() = Default::default();
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.heading("Render parameters ");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui
+                            .add_enabled(self.edits.can_redo(), Button::new("⟳ Redo"))
+                            .on_hover_text("Ctrl+Shift+Z")
+                            .clicked()
+                        {
+                            self.edits
+                                .redo(&mut self.render_box.scene, &mut self.params);
+                        }
+                        if ui
+                            .add_enabled(self.edits.can_undo(), Button::new("⟲ Undo"))
+                            .on_hover_text("Ctrl+Z")
+                            .clicked()
+                        {
+                            self.edits
+                                .undo(&mut self.render_box.scene, &mut self.params);
+                        }
+                    });
+                });
 
 
-                egui::ComboBox::from_label("Render resolution")
-                    .selected_text(format!("{}", self.params.resolution))
+                self.resolution_editor.show(ui, &mut self.params);
+                ui.horizontal(|ui| {
+                    let view = &self.params.camera;
+                    let [x, y, z] = view.eye().data;
+                    ui.label(format!(
+                        "Camera at ({:.2}, {:.2}, {:.2}), yaw {:.0}°, pitch {:.0}°",
+                        x,
+                        y,
+                        z,
+                        view.yaw.to_degrees(),
+                        view.pitch.to_degrees()
+                    ));
+                    if ui.button("Reset camera").clicked() {
+                        self.params.camera = CameraView::default();
+                        self.render_box.request_render(self.params.clone());
+                    }
+                });
+                egui::ComboBox::from_label("Projection")
+                    .selected_text(format!("{}", self.params.projection))
                     .show_ui(ui, |ui| {
-                        for res in &self.params.available_resolutions {
+                        for projection in Projection::ALL {
                             ui.selectable_value(
-                                &mut self.params.resolution,
-                                *res,
-                                format!("{}", res),
+                                &mut self.params.projection,
+                                projection,
+                                format!("{}", projection),
                             );
                         }
                     });
@@ -71,33 +679,75 @@ impl eframe::App for MyApp {
                     egui::Slider::new(&mut self.params.focal_length, 0.0..=1.0)
                         .text("Focal length"),
                 );
+                ui.add(egui::Slider::new(&mut self.params.aperture, 0.0..=0.5).text("Aperture"));
+                ui.add(
+                    egui::Slider::new(&mut self.params.focus_distance, 0.1..=10.0)
+                        .text("Focus distance"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut self.params.shutter_open, 0.0..=1.0)
+                        .text("Shutter open"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut self.params.shutter_close, 0.0..=1.0)
+                        .text("Shutter close"),
+                );
                 ui.add(
                     egui::Slider::new(&mut self.params.samples, 0..=1000).text("Number of samples"),
                 );
+                egui::ComboBox::from_label("Sampler")
+                    .selected_text(format!("{}", self.params.sampler))
+                    .show_ui(ui, |ui| {
+                        for sampler in SamplerKind::ALL {
+                            ui.selectable_value(
+                                &mut self.params.sampler,
+                                sampler,
+                                format!("{}", sampler),
+                            );
+                        }
+                    });
+                egui::ComboBox::from_label("Pixel filter")
+                    .selected_text(format!("{}", self.params.filter.kind))
+                    .show_ui(ui, |ui| {
+                        for kind in FilterKind::ALL {
+                            ui.selectable_value(
+                                &mut self.params.filter.kind,
+                                kind,
+                                format!("{}", kind),
+                            );
+                        }
+                    });
+                ui.add(
+                    egui::Slider::new(&mut self.params.filter.radius, 0.5..=4.0)
+                        .text("Filter radius"),
+                );
+                ui.checkbox(&mut self.params.adaptive_sampling, "Adaptive sampling");
+                ui.add_enabled(
+                    self.params.adaptive_sampling,
+                    egui::Slider::new(&mut self.params.min_samples, 2..=256)
+                        .text("Minimum samples"),
+                );
+                ui.add_enabled(
+                    self.params.adaptive_sampling,
+                    egui::Slider::new(&mut self.params.adaptive_threshold, 0.001..=0.2)
+                        .logarithmic(true)
+                        .text("Error threshold"),
+                );
+                ui.checkbox(&mut self.params.denoise, "Denoise");
 
                 ui.add(
                     egui::Slider::new(&mut self.params.min_ray_distance, 0.0001..=0.1)
                         .text("Min ray distance"),
                 );
+                if let Some(stats) = &self.render_box.last_stats {
+                    ui.separator();
+                    egui::CollapsingHeader::new("Render statistics")
+                        .default_open(true)
+                        .show(ui, |ui| stats_grid(ui, stats));
+                }
                 ui.separator();
                 ui.heading("Scene contents ");
-                let mut id = 1;
-                for object in &mut self.render_box.scene.contents {
-                    id += 1;
-                    ui.push_id(id, |ui| match object {
-                        SceneObject::Sphere(sphere) => {
-                            ui.collapsing(sphere.name(), |ui2| {
-                                ui2.add(
-                                    egui::Slider::new(&mut sphere.radius, 0.0..=sphere.max_radius)
-                                        .text("Sphere radius"),
-                                );
-                                let mut x = sphere.color.into();
-                                ui2.color_edit_button_rgb(&mut x);
-                                sphere.color = x.into()
-                            });
-                        }
-                    });
-                }
+                self.outliner.show(ui, &mut self.render_box.scene);
                 ui.separator();
                 ui.vertical_centered(|ui| {
                     let button = Button::new("Render")
@@ -111,9 +761,36 @@ impl eframe::App for MyApp {
                     }
                 })
             });
+        egui::TopBottomPanel::bottom("timeline").show(ctx, |ui| {
+            let rendering_sequence = self.render_box.rendering_sequence();
+            let action = self.timeline.show(
+                ui,
+                &mut self.render_box.scene,
+                &mut self.params.camera,
+                self.outliner.selected,
+                rendering_sequence,
+            );
+            match action {
+                Some(TimelineAction::FrameChanged) => {
+                    self.render_box.request_preview(&mut self.params)
+                }
+                Some(TimelineAction::RenderSequence(pattern)) => {
+                    self.render_box.render_sequence(&mut self.params, pattern)
+                }
+                Some(TimelineAction::CancelSequence) => self.render_box.cancel_sequence(),
+                None => {}
+            }
+            if let Some(status) = &self.render_box.sequence_status {
+                ui.label(status);
+            }
+        });
         egui::CentralPanel::default().show(ctx, |ui| {
-            self.render_box.draw(ui);
+            self.render_box
+                .draw(ui, &mut self.params, &mut self.outliner.selected);
         });
+        let animating = self.timeline.playing() || self.render_box.rendering_sequence();
+        self.edits
+            .update(ctx, &mut self.render_box.scene, &mut self.params, animating);
         ctx.request_repaint();
     }
 }
@@ -0,0 +1,102 @@
+use crate::app::scene_files::SceneFile;
+use crate::renderer::animation::save_frame;
+use crate::renderer::Renderer;
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: raytracer-rs --render-sequence <scene.ron> [--frames <start>..<end>] \
+                     [--output <pattern>]\n\
+                     Renders the frames (default: the scene's frame range) to numbered PNGs, \
+                     `#` in the pattern is replaced by the frame number (default: frame_####.png)";
+
+///
+/// Command line options for rendering an animation without the UI
+///
+struct SequenceOptions {
+    scene: PathBuf,
+    frames: Option<(u32, u32)>,
+    output: String,
+}
+
+impl SequenceOptions {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut scene = None;
+        let mut frames = None;
+        let mut output = "frame_####.png".to_string();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value of {}", arg))
+            };
+            match arg.as_str() {
+                "--render-sequence" => scene = Some(PathBuf::from(value()?)),
+                "--frames" => {
+                    let range = value()?;
+                    let (start, end) = range
+                        .split_once("..")
+                        .ok_or_else(|| format!("Invalid frame range {}", range))?;
+                    let parse = |frame: &str| {
+                        frame
+                            .parse::<u32>()
+                            .map_err(|_| format!("Invalid frame range {}", range))
+                    };
+                    frames = Some((parse(start)?, parse(end)?));
+                }
+                "--output" => output = value()?.clone(),
+                _ => return Err(format!("Unknown argument {}", arg)),
+            }
+        }
+        Ok(Self {
+            scene: scene.ok_or_else(|| "Missing --render-sequence".to_string())?,
+            frames,
+            output,
+        })
+    }
+}
+
+///
+/// Run the command line interface, returns the process exit code
+///
+pub fn run(args: &[String]) -> i32 {
+    let options = match SequenceOptions::parse(args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            return 2;
+        }
+    };
+    let file = match SceneFile::load(&options.scene) {
+        Ok(file) => file,
+        Err(error) => {
+            eprintln!("Unable to open {}: {}", options.scene.display(), error);
+            return 1;
+        }
+    };
+
+    let SceneFile {
+        mut scene,
+        mut params,
+    } = file;
+    params.region = None;
+    let (start, end) = options
+        .frames
+        .unwrap_or((scene.animation.start, scene.animation.end));
+    let mut renderer = Renderer::create();
+    for frame in start..=end {
+        scene.set_frame(frame, &mut params.camera);
+        let rendered = renderer.render_blocking(params.clone(), &scene);
+        match save_frame(&rendered.image, &options.output, frame) {
+            Ok(path) => println!(
+                "Frame {} saved to {}\n{}",
+                frame,
+                path.display(),
+                rendered.stats
+            ),
+            Err(error) => {
+                eprintln!("Unable to save frame {}: {}", frame, error);
+                return 1;
+            }
+        }
+    }
+    0
+}
@@ -9,10 +9,16 @@ use eframe::{egui, HardwareAcceleration};
 use egui::{Color32, Rounding, Vec2};
 
 mod app;
+mod cli;
 mod math;
 mod renderer;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
     let mut options = eframe::NativeOptions::default();
     options.hardware_acceleration = HardwareAcceleration::Required;
     options.initial_window_size = Some(Vec2::new(1600.0, 1200.0));
@@ -32,7 +38,7 @@ fn main() {
             ctx.set_style(style);
 
 
-            Box::new(MyApp::default())
+            Box::new(MyApp::new(cc))
         }),
     );
 }
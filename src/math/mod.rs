@@ -1,6 +1,8 @@
+mod polynomial;
 mod ray;
 mod vec3;
 
+pub use polynomial::{solve_quadratic, solve_quartic};
 pub use ray::Ray;
 pub use vec3::Color3;
 pub use vec3::Point3;
@@ -0,0 +1,212 @@
+//!
+//! Real roots of low degree polynomials, closed form solutions after J. Schwarze
+//! (Graphics Gems I, "Cubic and Quartic Roots")
+//!
+
+use std::f64::consts::PI;
+
+const EPSILON: f64 = 1e-9;
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < EPSILON
+}
+
+///
+/// Real roots of a x² + b x + c in ascending order. The coefficients can have any scale
+/// (like ones from an unnormalized ray direction), so the equation is only treated as
+/// linear when `a` vanishes next to the other coefficients.
+///
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() <= f64::EPSILON * (b.abs() + c.abs()) {
+        return match b == 0.0 {
+            true => vec![],
+            false => vec![-c / b],
+        };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    // avoids cancellation when b and the square root are close
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let mut roots = match q == 0.0 {
+        true => vec![0.0, 0.0],
+        false => vec![q / a, c / q],
+    };
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+/// Real roots of x³ + a x² + b x + c
+fn solve_cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    // substitute x = y - a/3 to eliminate the quadratic term: y³ + 3p y + 2q = 0
+    let sq_a = a * a;
+    let p = (-sq_a / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * sq_a - a * b / 3.0 + c) / 2.0;
+    let cb_p = p * p * p;
+    let discriminant = q * q + cb_p;
+
+    let roots = if is_zero(discriminant) {
+        match is_zero(q) {
+            true => vec![0.0],
+            false => {
+                let u = (-q).cbrt();
+                vec![2.0 * u, -u]
+            }
+        }
+    } else if discriminant < 0.0 {
+        // three real roots
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.0).cos(),
+            -t * (phi - PI / 3.0).cos(),
+        ]
+    } else {
+        let sqrt_d = discriminant.sqrt();
+        vec![(sqrt_d - q).cbrt() - (sqrt_d + q).cbrt()]
+    };
+    roots.into_iter().map(|y| y - a / 3.0).collect()
+}
+
+/// Real roots of a x⁴ + b x³ + c x² + d x + e in ascending order
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // substitute x = y - b/4 to eliminate the cubic term: y⁴ + p y² + q y + r = 0
+    let sq_b = b * b;
+    let p = -3.0 / 8.0 * sq_b + c;
+    let q = sq_b * b / 8.0 - b * c / 2.0 + d;
+    let r = -3.0 / 256.0 * sq_b * sq_b + sq_b * c / 16.0 - b * d / 4.0 + e;
+
+    let mut roots = if is_zero(r) {
+        // y (y³ + p y + q) = 0
+        let mut roots = solve_cubic(0.0, p, q);
+        roots.push(0.0);
+        roots
+    } else {
+        // one root of the resolvent cubic splits the quartic into two quadratics
+        let z = solve_cubic(-p / 2.0, -r, r * p / 2.0 - q * q / 8.0)[0];
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        let root = |x: f64| match is_zero(x) {
+            true => Some(0.0),
+            false => (x > 0.0).then(|| x.sqrt()),
+        };
+        match (root(u), root(v)) {
+            (Some(u), Some(v)) => {
+                let v = match q < 0.0 {
+                    true => -v,
+                    false => v,
+                };
+                let mut roots = solve_quadratic(1.0, v, z - u);
+                roots.extend(solve_quadratic(1.0, -v, z + u));
+                roots
+            }
+            _ => vec![],
+        }
+    };
+
+    // the closed form loses precision, polish the roots with Newton's method
+    for x in &mut roots {
+        *x -= b / 4.0;
+        for _ in 0..2 {
+            let value = (((*x + b) * *x + c) * *x + d) * *x + e;
+            let derivative = ((4.0 * *x + 3.0 * b) * *x + 2.0 * c) * *x + d;
+            if derivative != 0.0 {
+                *x -= value / derivative;
+            }
+        }
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(roots: Vec<f64>, expected: &[f64], tolerance: f64) {
+        assert_eq!(roots.len(), expected.len(), "roots {:?}", roots);
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!(
+                (root - expected).abs() < tolerance,
+                "roots {:?}, expected {:?}",
+                roots,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn quadratic_roots() {
+        assert_roots(solve_quadratic(1.0, -1.0, -6.0), &[-2.0, 3.0], 1e-12);
+        assert_roots(solve_quadratic(1.0, -2.0, 1.0), &[1.0, 1.0], 1e-12);
+        assert_roots(solve_quadratic(0.0, 2.0, -4.0), &[2.0], 1e-12);
+        assert_roots(solve_quadratic(1.0, 0.0, 1.0), &[], 0.0);
+        // (x - 1)(x - 2) scaled down, like for a short ray direction
+        assert_roots(solve_quadratic(1e-10, -3e-10, 2e-10), &[1.0, 2.0], 1e-9);
+        // direction nearly parallel to the axis of a cylinder
+        let half_root_7 = 7f64.sqrt() / 2.0;
+        assert_roots(
+            solve_quadratic(1e-12, 2e-6, -0.75),
+            &[(-1.0 - half_root_7) * 1e6, (-1.0 + half_root_7) * 1e6],
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn quartic_distinct_roots() {
+        // (x + 2)(x - 1)(x - 3)(x - 4)
+        assert_roots(
+            solve_quartic(1.0, -6.0, 3.0, 26.0, -24.0),
+            &[-2.0, 1.0, 3.0, 4.0],
+            1e-9,
+        );
+        // scaled leading coefficient
+        assert_roots(
+            solve_quartic(2.0, -12.0, 6.0, 52.0, -48.0),
+            &[-2.0, 1.0, 3.0, 4.0],
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn quartic_double_roots() {
+        // (x - 1)² (x - 2)²
+        assert_roots(
+            solve_quartic(1.0, -6.0, 13.0, -12.0, 4.0),
+            &[1.0, 1.0, 2.0, 2.0],
+            1e-4,
+        );
+    }
+
+    #[test]
+    fn quartic_biquadratic() {
+        // (x² - 1)(x² - 4) has no odd terms
+        assert_roots(
+            solve_quartic(1.0, 0.0, -5.0, 0.0, 4.0),
+            &[-2.0, -1.0, 1.0, 2.0],
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn quartic_without_real_roots() {
+        assert_roots(solve_quartic(1.0, 0.0, 0.0, 0.0, 1.0), &[], 0.0);
+        // (x² + 1)(x² + 2x + 5)
+        assert_roots(solve_quartic(1.0, 2.0, 6.0, 2.0, 5.0), &[], 0.0);
+    }
+
+    #[test]
+    fn quartic_ray_through_torus() {
+        // ray from (-5, 0, 0) along x through the torus of radii 2 and 0.5 around the
+        // origin, see Torus::roots for the coefficients
+        assert_roots(
+            solve_quartic(1.0, -20.0, 141.5, -415.0, 426.5625),
+            &[2.5, 3.5, 6.5, 7.5],
+            1e-9,
+        );
+    }
+}
@@ -4,11 +4,18 @@ use crate::math::Vec3;
 pub struct Ray {
     origin: Point3,
     direction: Vec3,
+    /// Moment within the shutter interval the ray samples, moving objects are hit where
+    /// they are at this time
+    time: f64,
 }
 
 impl Ray {
-    pub fn new(origin: Point3, direction: Vec3) -> Self {
-        Self { origin, direction }
+    pub fn new(origin: Point3, direction: Vec3, time: f64) -> Self {
+        Self {
+            origin,
+            direction,
+            time,
+        }
     }
 
     #[allow(dead_code)]
@@ -22,4 +29,7 @@ impl Ray {
     pub fn origin(&self) -> &Point3 {
         &self.origin
     }
+    pub fn time(&self) -> f64 {
+        self.time
+    }
 }
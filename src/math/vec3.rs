@@ -1,8 +1,10 @@
 use eframe::epaint::Color32;
+use serde::{Deserialize, Serialize};
 use std::fmt::{format, Display, Formatter};
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
-#[derive(Default, Copy, Clone, Debug)]
+#[derive(Default, Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Vec3 {
     pub(crate) data: [f64; 3],
 }
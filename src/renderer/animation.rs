@@ -0,0 +1,218 @@
+use crate::renderer::camera::CameraView;
+use crate::renderer::export::save_png;
+use crate::Vec3;
+use egui::ColorImage;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+
+///
+/// Values that can be interpolated between keyframes, interpolation only needs weighted
+/// sums of key values
+///
+pub trait Animatable: Clone {
+    fn weighted_sum(terms: [(&Self, f64); 4]) -> Self;
+}
+
+impl Animatable for f64 {
+    fn weighted_sum(terms: [(&Self, f64); 4]) -> Self {
+        terms.iter().map(|(value, weight)| *value * weight).sum()
+    }
+}
+
+impl Animatable for Vec3 {
+    fn weighted_sum(terms: [(&Self, f64); 4]) -> Self {
+        terms.iter().fold(Vec3::splat(0.0), |sum, (value, weight)| {
+            sum + **value * *weight
+        })
+    }
+}
+
+impl Animatable for CameraView {
+    fn weighted_sum(terms: [(&Self, f64); 4]) -> Self {
+        CameraView {
+            target: Vec3::weighted_sum(terms.map(|(view, weight)| (&view.target, weight))),
+            yaw: f64::weighted_sum(terms.map(|(view, weight)| (&view.yaw, weight))),
+            pitch: f64::weighted_sum(terms.map(|(view, weight)| (&view.pitch, weight))),
+            distance: f64::weighted_sum(terms.map(|(view, weight)| (&view.distance, weight))),
+        }
+    }
+}
+
+///
+/// How the value changes from a keyframe to the next one
+///
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Interpolation {
+    Linear,
+    /// Smooth curve through the neighbouring keys (Catmull-Rom tangents)
+    Bezier,
+}
+
+impl Interpolation {
+    pub const ALL: [Interpolation; 2] = [Interpolation::Linear, Interpolation::Bezier];
+}
+
+impl Display for Interpolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Interpolation::Linear => "Linear",
+            Interpolation::Bezier => "Bezier",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Keyframe<T> {
+    pub frame: u32,
+    pub value: T,
+    pub interpolation: Interpolation,
+}
+
+///
+/// Keyframes of one property sorted by frame, values before the first and after the last
+/// key are held constant
+///
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Track<T> {
+    keys: Vec<Keyframe<T>>,
+}
+
+impl<T> Default for Track<T> {
+    fn default() -> Self {
+        Self { keys: vec![] }
+    }
+}
+
+impl<T: Animatable> Track<T> {
+    pub fn keys(&self) -> &[Keyframe<T>] {
+        &self.keys
+    }
+
+    pub fn has_key(&self, frame: u32) -> bool {
+        self.keys.iter().any(|key| key.frame == frame)
+    }
+
+    /// Add key at the frame or replace the one already there
+    pub fn set_key(&mut self, frame: u32, value: T, interpolation: Interpolation) {
+        let key = Keyframe {
+            frame,
+            value,
+            interpolation,
+        };
+        match self.keys.binary_search_by_key(&frame, |key| key.frame) {
+            Ok(index) => self.keys[index] = key,
+            Err(index) => self.keys.insert(index, key),
+        }
+    }
+
+    pub fn remove_key(&mut self, frame: u32) {
+        self.keys.retain(|key| key.frame != frame);
+    }
+
+    /// Value at the (fractional) frame, `None` for a track without keys
+    pub fn sample(&self, frame: f64) -> Option<T> {
+        let first = self.keys.first()?;
+        let next = self.keys.partition_point(|key| key.frame as f64 <= frame);
+        if next == 0 {
+            return Some(first.value.clone());
+        }
+        if next == self.keys.len() {
+            return self.keys.last().map(|key| key.value.clone());
+        }
+
+        let (from, to) = (&self.keys[next - 1], &self.keys[next]);
+        let t = (frame - from.frame as f64) / (to.frame - from.frame) as f64;
+        let before = &self.keys[next.saturating_sub(2)];
+        let after = &self.keys[(next + 1).min(self.keys.len() - 1)];
+        Some(match from.interpolation {
+            Interpolation::Linear => T::weighted_sum([
+                (&from.value, 1.0 - t),
+                (&to.value, t),
+                (&before.value, 0.0),
+                (&after.value, 0.0),
+            ]),
+            Interpolation::Bezier => {
+                // Bernstein weights of the control points from, from + (to - before) / 6,
+                // to - (after - from) / 6 and to, expanded into weights of the keys
+                let s = 1.0 - t;
+                let b = [s * s * s, 3.0 * t * s * s, 3.0 * t * t * s, t * t * t];
+                T::weighted_sum([
+                    (&before.value, -b[1] / 6.0),
+                    (&from.value, b[0] + b[1] + b[2] / 6.0),
+                    (&to.value, b[1] / 6.0 + b[2] + b[3]),
+                    (&after.value, -b[2] / 6.0),
+                ])
+            }
+        })
+    }
+}
+
+///
+/// Animated properties of a scene object
+///
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct ObjectTracks {
+    pub position: Track<Vec3>,
+    pub color: Track<Vec3>,
+}
+
+///
+/// Frame range and camera animation of the scene
+///
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Animation {
+    pub start: u32,
+    pub end: u32,
+    /// Frame the scene and camera are currently posed at
+    pub current: u32,
+    pub fps: f64,
+    pub camera: Track<CameraView>,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self {
+            start: 0,
+            end: 100,
+            current: 0,
+            fps: 24.0,
+            camera: Track::default(),
+        }
+    }
+}
+
+///
+/// Path of the frame's image: the first run of `#` in the pattern is replaced by the zero
+/// padded frame number, patterns without `#` get the number appended
+///
+pub fn frame_path(pattern: &str, frame: u32) -> PathBuf {
+    match pattern.find('#') {
+        Some(start) => {
+            let width = pattern[start..].chars().take_while(|c| *c == '#').count();
+            PathBuf::from(format!(
+                "{}{:0width$}{}",
+                &pattern[..start],
+                frame,
+                &pattern[start + width..],
+                width = width
+            ))
+        }
+        None => PathBuf::from(format!("{}{:04}.png", pattern, frame)),
+    }
+}
+
+///
+/// Save image of an animation frame to its [`frame_path`], creating missing directories
+///
+pub fn save_frame(image: &ColorImage, pattern: &str, frame: u32) -> io::Result<PathBuf> {
+    let path = frame_path(pattern, frame);
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    save_png(image, &path)?;
+    Ok(path)
+}
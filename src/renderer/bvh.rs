@@ -0,0 +1,378 @@
+use crate::math::Point3;
+use crate::renderer::scene::count_intersection_tests;
+use crate::{Ray, Vec3};
+use std::ops::Range;
+
+/// Number of items below which nodes aren't split any further
+const MAX_LEAF_ITEMS: usize = 2;
+
+///
+/// Axis aligned bounding box
+///
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Aabb {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl Aabb {
+    pub fn new(min: Point3, max: Point3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn around_sphere(center: Point3, radius: f64) -> Self {
+        let radius = Vec3::splat(radius.abs());
+        Self::new(center - radius, center + radius)
+    }
+
+    /// Smallest box containing all the points
+    pub fn around_points(points: impl IntoIterator<Item = Point3>) -> Self {
+        let mut points = points.into_iter();
+        let first = points.next().expect("Box needs at least one point");
+        points.fold(Self::new(first, first), |bounds, point| {
+            bounds.union(&Self::new(point, point))
+        })
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Vec3::from([0, 1, 2].map(|i| self.min.data[i].min(other.min.data[i]))),
+            max: Vec3::from([0, 1, 2].map(|i| self.max.data[i].max(other.max.data[i]))),
+        }
+    }
+
+    pub fn center(&self) -> Point3 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn corners(&self) -> [Point3; 8] {
+        [0, 1, 2, 3, 4, 5, 6, 7].map(|corner: usize| {
+            Vec3::from([0, 1, 2].map(|i| match corner >> i & 1 {
+                0 => self.min.data[i],
+                _ => self.max.data[i],
+            }))
+        })
+    }
+
+    /// Slab test, true if the ray passes through the box between `t_min` and `t_max`
+    pub fn hit(&self, ray: &Ray, mut t_min: f64, mut t_max: f64) -> bool {
+        for axis in 0..3 {
+            let inverse_direction = 1.0 / ray.direction().data[axis];
+            let mut t0 = (self.min.data[axis] - ray.origin().data[axis]) * inverse_direction;
+            let mut t1 = (self.max.data[axis] - ray.origin().data[axis]) * inverse_direction;
+            if inverse_direction < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // NaN from a ray lying in a slab boundary keeps the interval unchanged
+            if t0 > t_min {
+                t_min = t0;
+            }
+            if t1 < t_max {
+                t_max = t1;
+            }
+            if t_max < t_min {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum BvhNode {
+    Leaf {
+        bounds: Aabb,
+        items: Range<usize>,
+    },
+    Inner {
+        bounds: Aabb,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl BvhNode {
+    fn bounds(&self) -> &Aabb {
+        match self {
+            BvhNode::Leaf { bounds, .. } | BvhNode::Inner { bounds, .. } => bounds,
+        }
+    }
+}
+
+///
+/// Bounding volume hierarchy over a list of items given by their bounding boxes. Items
+/// are referred to by their index in that list, unbounded items are tested by every ray.
+///
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    /// Item indices ordered so that each leaf covers a contiguous range
+    items: Vec<usize>,
+    unbounded: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn build(bounds: &[Option<Aabb>]) -> Self {
+        let mut bounded: Vec<(usize, Aabb)> = vec![];
+        let mut unbounded = vec![];
+        for (index, item_bounds) in bounds.iter().enumerate() {
+            match item_bounds {
+                Some(item_bounds) => bounded.push((index, *item_bounds)),
+                None => unbounded.push(index),
+            }
+        }
+        let mut bvh = Self {
+            nodes: vec![],
+            items: vec![],
+            unbounded,
+        };
+        if !bounded.is_empty() {
+            bvh.build_node(&mut bounded);
+        }
+        bvh
+    }
+
+    /// Adds node for the items and its subtree, returns index of the node
+    fn build_node(&mut self, items: &mut [(usize, Aabb)]) -> usize {
+        let bounds = items
+            .iter()
+            .skip(1)
+            .fold(items[0].1, |bounds, (_, item)| bounds.union(item));
+        let index = self.nodes.len();
+        if items.len() <= MAX_LEAF_ITEMS {
+            let start = self.items.len();
+            self.items.extend(items.iter().map(|(item, _)| *item));
+            self.nodes.push(BvhNode::Leaf {
+                bounds,
+                items: start..self.items.len(),
+            });
+            return index;
+        }
+
+        // split at the median of box centers along the axis they are most spread on
+        let centers = Aabb::around_points(items.iter().map(|(_, item)| item.center()));
+        let extent = centers.max - centers.min;
+        let axis = (0..3)
+            .max_by(|a, b| extent.data[*a].total_cmp(&extent.data[*b]))
+            .unwrap_or(0);
+        items.sort_by(|(_, a), (_, b)| a.center().data[axis].total_cmp(&b.center().data[axis]));
+        let (left_items, right_items) = items.split_at_mut(items.len() / 2);
+
+        // children are filled in after the subtrees are built
+        self.nodes.push(BvhNode::Leaf {
+            bounds,
+            items: 0..0,
+        });
+        let left = self.build_node(left_items);
+        let right = self.build_node(right_items);
+        self.nodes[index] = BvhNode::Inner {
+            bounds,
+            left,
+            right,
+        };
+        index
+    }
+
+    ///
+    /// Closest hit of the ray, `test` checks a single item against the ray up to the
+    /// distance given as its second argument and returns the hit with its distance. Calls
+    /// of `test` are counted as intersection tests.
+    ///
+    pub fn closest<T>(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        mut test: impl FnMut(usize, f64) -> Option<(f64, T)>,
+    ) -> Option<T> {
+        let mut closest_so_far = t_max;
+        let mut closest = None;
+        let mut tests = 0;
+        let mut visit = |item: usize, closest_so_far: &mut f64| {
+            tests += 1;
+            if let Some((t, hit)) = test(item, *closest_so_far) {
+                *closest_so_far = t;
+                closest = Some(hit);
+            }
+        };
+        for item in &self.unbounded {
+            visit(*item, &mut closest_so_far);
+        }
+
+        let mut stack = match self.nodes.is_empty() {
+            true => vec![],
+            false => vec![0],
+        };
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if !node.bounds().hit(ray, t_min, closest_so_far) {
+                continue;
+            }
+            match node {
+                BvhNode::Leaf { items, .. } => {
+                    for item in &self.items[items.clone()] {
+                        visit(*item, &mut closest_so_far);
+                    }
+                }
+                BvhNode::Inner { left, right, .. } => {
+                    stack.push(*right);
+                    stack.push(*left);
+                }
+            }
+        }
+        count_intersection_tests(tests);
+        closest
+    }
+
+    ///
+    /// Items whose boxes the ray (extended in both directions) passes through, each is
+    /// counted as an intersection test
+    ///
+    pub fn candidates(&self, ray: &Ray) -> Vec<usize> {
+        let mut candidates = self.unbounded.clone();
+        let mut stack = match self.nodes.is_empty() {
+            true => vec![],
+            false => vec![0],
+        };
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if !node.bounds().hit(ray, f64::NEG_INFINITY, f64::INFINITY) {
+                continue;
+            }
+            match node {
+                BvhNode::Leaf { items, .. } => candidates.extend(&self.items[items.clone()]),
+                BvhNode::Inner { left, right, .. } => {
+                    stack.push(*right);
+                    stack.push(*left);
+                }
+            }
+        }
+        count_intersection_tests(candidates.len() as u64);
+        candidates
+    }
+
+    /// Bounds of everything in the hierarchy, `None` when it has unbounded items
+    pub fn bounds(&self) -> Option<Aabb> {
+        match self.unbounded.is_empty() {
+            true => self.nodes.first().map(|node| *node.bounds()),
+            false => None,
+        }
+    }
+
+    /// Bytes used by the hierarchy
+    pub fn memory_size(&self) -> usize {
+        self.nodes.capacity() * std::mem::size_of::<BvhNode>() +
+            (self.items.capacity() + self.unbounded.capacity()) * std::mem::size_of::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Unit boxes along the x axis, box `i` spans `[2i, 2i + 1]`
+    fn row_of_boxes(count: usize) -> Vec<Option<Aabb>> {
+        (0..count)
+            .map(|i| {
+                let min = Point3::new(2.0 * i as f64, 0.0, 0.0);
+                Some(Aabb::new(min, min + Vec3::splat(1.0)))
+            })
+            .collect()
+    }
+
+    /// Hits the box where the ray enters it
+    fn hit_box(
+        bounds: &[Option<Aabb>],
+        ray: &Ray,
+        index: usize,
+        t_max: f64,
+    ) -> Option<(f64, usize)> {
+        let bounds = bounds[index]?;
+        let t = (bounds.min.x() - ray.origin().x()) / ray.direction().x();
+        (t > 0.0 && t < t_max && bounds.hit(ray, 0.0, t_max)).then_some((t, index))
+    }
+
+    #[test]
+    fn build_keeps_every_item() {
+        let bvh = Bvh::build(&row_of_boxes(13));
+        let mut items = bvh.items.clone();
+        items.sort();
+        assert_eq!(items, (0..13).collect::<Vec<_>>());
+        let bounds = bvh.bounds().unwrap();
+        assert_eq!(bounds.min, Point3::new(0.0, 0.0, 0.0));
+        assert_eq!(bounds.max, Point3::new(25.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn closest_finds_nearest_item() {
+        let bounds = row_of_boxes(13);
+        let bvh = Bvh::build(&bounds);
+        let origin = Point3::new(-1.0, 0.5, 0.5);
+        let forward = Ray::new(origin, Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = bvh.closest(&forward, 0.0, f64::INFINITY, |index, t_max| {
+            hit_box(&bounds, &forward, index, t_max)
+        });
+        assert_eq!(hit, Some(0));
+
+        let start = Point3::new(13.5, 0.5, 0.5);
+        let backward = Ray::new(start, Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        let hit = bvh.closest(&backward, 0.0, f64::INFINITY, |index, t_max| {
+            let bounds = bounds[index]?;
+            let t = start.x() - bounds.max.x();
+            (t > 0.0 && t < t_max).then_some((t, index))
+        });
+        assert_eq!(hit, Some(6));
+
+        let above = Ray::new(Point3::new(-1.0, 5.0, 0.5), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let mut tests = 0;
+        let hit = bvh.closest(&above, 0.0, f64::INFINITY, |index, t_max| {
+            tests += 1;
+            hit_box(&bounds, &above, index, t_max)
+        });
+        assert_eq!(hit, None);
+        assert_eq!(tests, 0);
+    }
+
+    #[test]
+    fn unbounded_items_are_always_tested() {
+        let mut bounds = row_of_boxes(4);
+        bounds.insert(2, None);
+        let bvh = Bvh::build(&bounds);
+        assert_eq!(bvh.bounds(), None);
+
+        let ray = Ray::new(Point3::new(-1.0, 5.0, 0.5), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = bvh.closest(&ray, 0.0, f64::INFINITY, |index, _| {
+            (index == 2).then_some((1.0, index))
+        });
+        assert_eq!(hit, Some(2));
+        assert_eq!(bvh.candidates(&ray), vec![2]);
+    }
+
+    #[test]
+    fn candidates_along_the_whole_line() {
+        let bvh = Bvh::build(&row_of_boxes(8));
+        // starts past the boxes and points away, the line still passes through all of them
+        let ray = Ray::new(Point3::new(20.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let mut candidates = bvh.candidates(&ray);
+        candidates.sort();
+        assert_eq!(candidates, (0..8).collect::<Vec<_>>());
+
+        // leaves are tested as a whole, so the neighbour of the box can come along
+        let vertical = Ray::new(Point3::new(0.5, -1.0, 0.5), Vec3::new(0.0, 1.0, 0.0), 0.0);
+        let candidates = bvh.candidates(&vertical);
+        assert!(candidates.contains(&0));
+        assert!(candidates.len() <= MAX_LEAF_ITEMS);
+    }
+
+    #[test]
+    fn empty_hierarchy() {
+        let bvh = Bvh::build(&[]);
+        let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        assert_eq!(
+            bvh.closest(&ray, 0.0, f64::INFINITY, |index, _| Some((1.0, index))),
+            None
+        );
+        assert!(bvh.candidates(&ray).is_empty());
+        assert_eq!(bvh.bounds(), None);
+    }
+}
@@ -1,43 +1,272 @@
 use crate::math::Point3;
+use crate::renderer::sampler::concentric_disk;
 use crate::{Ray, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use std::fmt::{Display, Formatter};
+
+/// Pitch is kept slightly below the poles so the view never flips over
+const MAX_PITCH: f64 = std::f64::consts::FRAC_PI_2 - 0.01;
+const MIN_DISTANCE: f64 = 0.01;
+/// Orthographic rays start at least this far behind the target, so nothing in the scene is
+/// clipped by the plane they start from
+const ORTHOGRAPHIC_DEPTH: f64 = 1000.0;
+
+///
+/// Position and orientation of the camera, stored as an orbit around the `target` point
+/// so the preview can be orbited, panned and dollied without accumulating roll
+///
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct CameraView {
+    pub target: Point3,
+    /// Rotation around the world up axis, in radians
+    pub yaw: f64,
+    /// Elevation above the horizon, in radians
+    pub pitch: f64,
+    /// Distance of the eye from the target
+    pub distance: f64,
+}
+
+impl Default for CameraView {
+    fn default() -> Self {
+        // eye at the origin looking down -z, as the original fixed camera did
+        Self {
+            target: Point3::new(0.0, 0.0, -1.0),
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: 1.0,
+        }
+    }
+}
+
+impl CameraView {
+    pub fn eye(&self) -> Point3 {
+        self.target - self.forward() * self.distance
+    }
+
+    /// Unit vector from the eye towards the target
+    pub fn forward(&self) -> Vec3 {
+        Vec3::new(
+            -self.pitch.cos() * self.yaw.sin(),
+            -self.pitch.sin(),
+            -self.pitch.cos() * self.yaw.cos(),
+        )
+    }
+
+    pub fn right(&self) -> Vec3 {
+        Vec3::new(self.yaw.cos(), 0.0, -self.yaw.sin())
+    }
+
+    pub fn up(&self) -> Vec3 {
+        Vec3::cross(&self.right(), &self.forward())
+    }
+
+    /// Rotate the eye around the target
+    pub fn orbit(&mut self, yaw: f64, pitch: f64) {
+        self.yaw = (self.yaw + yaw) % std::f64::consts::TAU;
+        self.pitch = (self.pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Move eye and target in the view plane, offsets are relative to the target distance
+    pub fn pan(&mut self, right: f64, up: f64) {
+        self.target =
+            self.target + self.right() * (right * self.distance) + self.up() * (up * self.distance);
+    }
+
+    /// Move the eye towards (`factor` < 1) or away from the target
+    pub fn dolly(&mut self, factor: f64) {
+        self.distance = (self.distance * factor).max(MIN_DISTANCE);
+    }
+
+    /// Move eye and target together along the camera axes
+    pub fn fly(&mut self, right: f64, up: f64, forward: f64) {
+        self.target =
+            self.target + self.right() * right + Vec3::new(0.0, up, 0.0) + self.forward() * forward;
+    }
+
+    /// Rotate the view around the eye, like turning the head
+    pub fn look(&mut self, yaw: f64, pitch: f64) {
+        let eye = self.eye();
+        self.orbit(yaw, pitch);
+        self.target = eye + self.forward() * self.distance;
+    }
+}
+
+///
+/// How the camera maps image points to ray directions
+///
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Projection {
+    Perspective,
+    /// Parallel rays, the image is framed like the perspective view at the target
+    Orthographic,
+    /// Equidistant fisheye, the image height spans 180°
+    Fisheye,
+    /// Full 360° panorama, longitude across the width and latitude across the height
+    Equirectangular,
+    /// Six 90° views in a 3×2 grid, right, left and up in the top row and down, front and
+    /// back in the bottom one, faces are square for 3:2 images
+    CubeMap,
+}
+
+impl Projection {
+    pub const ALL: [Projection; 5] = [
+        Projection::Perspective,
+        Projection::Orthographic,
+        Projection::Fisheye,
+        Projection::Equirectangular,
+        Projection::CubeMap,
+    ];
+}
+
+impl Display for Projection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Projection::Perspective => "Perspective",
+            Projection::Orthographic => "Orthographic",
+            Projection::Fisheye => "Fisheye",
+            Projection::Equirectangular => "Equirectangular (360°)",
+            Projection::CubeMap => "Cube map (360°)",
+        };
+        write!(f, "{}", name)
+    }
+}
 
 pub struct Camera {
+    projection: Projection,
     origin: Point3,
+    forward: Vec3,
     lower_left_corner: Vec3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    focal_length: f64,
+    lens_radius: f64,
+    focus_distance: f64,
+    /// Half height of the orthographic view
+    orthographic_size: f64,
+    /// How far behind the eye orthographic rays start
+    orthographic_offset: f64,
     pub(crate) viewport_width: f64,
     pub(crate) viewport_height: f64,
 }
 
 impl Camera {
-    pub fn new(viewport_size: [usize; 2], focal_length: f64) -> Self {
+    pub fn new(
+        viewport_size: [usize; 2],
+        view: &CameraView,
+        projection: Projection,
+        focal_length: f64,
+        aperture: f64,
+        focus_distance: f64,
+    ) -> Self {
         let image_width = viewport_size[0] as f64;
         let image_height = viewport_size[1] as f64;
         let aspect_ratio = image_width / image_height;
 
         let viewport_height: f64 = 2.0;
         let viewport_width = aspect_ratio * viewport_height;
-        let origin = Point3::new(0.0, 0.0, 0.0);
+        let origin = view.eye();
+        let horizontal = view.right();
+        let vertical = view.up();
 
-        // let lower_left_corner = origin - (horizontal / 2.0) - (vertical / 2.0) - Vec3::new(0.0, 0.0, focal_length);
-        let lower_left_corner = Vec3::new(
-            origin.x() - (viewport_width / 2.0),
-            origin.y() - (viewport_height / 2.0),
-            origin.z() - focal_length,
-        );
+        let lower_left_corner =
+            origin - horizontal * (viewport_width / 2.0) - vertical * (viewport_height / 2.0) +
+                view.forward() * focal_length;
 
         Self {
+            projection,
             origin,
+            forward: view.forward(),
             lower_left_corner,
+            horizontal,
+            vertical,
+            focal_length,
+            lens_radius: aperture / 2.0,
+            focus_distance,
+            orthographic_size: view.distance * (viewport_height / 2.0) / focal_length.max(0.01),
+            orthographic_offset: view.distance.max(ORTHOGRAPHIC_DEPTH) - view.distance,
             viewport_width,
             viewport_height,
         }
     }
 
-    pub fn cast_ray(&self, u: f64, v: f64) -> Ray {
-        let ray = Ray::new(
-            self.origin,
-            self.lower_left_corner + Vec3::new(u, v, 0.0) - self.origin,
-        );
-        ray
+    ///
+    /// Cast ray through viewport point (u, v), `lens` is a sample in [0, 1)² selecting
+    /// the point on the lens the ray starts from (only the perspective projection has a
+    /// lens, it matters for non-zero aperture)
+    ///
+    pub fn cast_ray(&self, u: f64, v: f64, lens: [f64; 2], time: f64) -> Ray {
+        // point relative to the viewport center, the height spans [-1, 1]
+        let half_height = self.viewport_height / 2.0;
+        let x = (u - self.viewport_width / 2.0) / half_height;
+        let y = (v - half_height) / half_height;
+        match self.projection {
+            Projection::Perspective => self.perspective_ray(u, v, lens, time),
+            Projection::Orthographic => {
+                let origin = self.origin - self.forward * self.orthographic_offset +
+                    self.horizontal * (x * self.orthographic_size) +
+                    self.vertical * (y * self.orthographic_size);
+                Ray::new(origin, self.forward, time)
+            }
+            Projection::Fisheye => {
+                // angle from the view axis grows linearly with the distance from the center
+                let radius = (x * x + y * y).sqrt();
+                let (sin, cos) = (radius * FRAC_PI_2).sin_cos();
+                let side = match radius > 0.0 {
+                    true => (self.horizontal * x + self.vertical * y) / radius,
+                    false => Vec3::splat(0.0),
+                };
+                Ray::new(self.origin, self.forward * cos + side * sin, time)
+            }
+            Projection::Equirectangular => {
+                let longitude = (u / self.viewport_width - 0.5) * TAU;
+                let latitude = (v / self.viewport_height - 0.5) * PI;
+                let around = self.forward * longitude.cos() + self.horizontal * longitude.sin();
+                let direction = around * latitude.cos() + self.vertical * latitude.sin();
+                Ray::new(self.origin, direction, time)
+            }
+            Projection::CubeMap => {
+                let column = (u / self.viewport_width * 3.0).clamp(0.0, 2.999);
+                let row = (v / self.viewport_height * 2.0).clamp(0.0, 1.999);
+                // point on the face, both in [-1, 1]
+                let a = column.fract() * 2.0 - 1.0;
+                let b = row.fract() * 2.0 - 1.0;
+                let (forward, right, up) = (self.forward, self.horizontal, self.vertical);
+                // faces turn the view, their image right and up axes turn along with it
+                let direction = match (row as usize, column as usize) {
+                    (1, 0) => right - forward * a + up * b,
+                    (1, 1) => -right + forward * a + up * b,
+                    (1, _) => up + right * a - forward * b,
+                    (_, 0) => -up + right * a + forward * b,
+                    (_, 1) => forward + right * a + up * b,
+                    (_, _) => -forward - right * a + up * b,
+                };
+                Ray::new(self.origin, direction, time)
+            }
+        }
+    }
+
+    /// Distance of a hit from the eye, given its `distance` along a ray of this camera
+    pub fn eye_distance(&self, distance: f64) -> f64 {
+        match self.projection {
+            // objects between the ray start and the eye count as touching the eye
+            Projection::Orthographic => (distance - self.orthographic_offset).max(0.0),
+            _ => distance,
+        }
+    }
+
+    fn perspective_ray(&self, u: f64, v: f64, lens: [f64; 2], time: f64) -> Ray {
+        let direction =
+            self.lower_left_corner + self.horizontal * u + self.vertical * v - self.origin;
+        if self.lens_radius <= 0.0 {
+            return Ray::new(self.origin, direction, time);
+        }
+
+        let focus_point = self.origin + direction * (self.focus_distance / self.focal_length);
+        let disk = concentric_disk(lens);
+        let lens_origin = self.origin +
+            self.horizontal * (disk[0] * self.lens_radius) +
+            self.vertical * (disk[1] * self.lens_radius);
+        Ray::new(lens_origin, focus_point - lens_origin, time)
     }
 }
@@ -0,0 +1,142 @@
+use crate::renderer::film::luminance;
+use crate::renderer::frame::Frame;
+use crate::Color3;
+use std::thread;
+
+const ITERATIONS: usize = 5;
+const KERNEL: [f64; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+/// How many standard deviations of pixel noise are still treated as the same color
+const COLOR_SIGMA: f64 = 1.0;
+const NORMAL_PHI: f64 = 0.1;
+const DEPTH_PHI: f64 = 0.02;
+const ALBEDO_PHI: f64 = 0.05;
+
+/// Albedo floor used when dividing it out of the color
+const MIN_ALBEDO: f64 = 0.01;
+
+struct Guide<'a> {
+    width: usize,
+    frame: &'a Frame,
+    /// Pixels `[min, max)` being filtered, the rest of the frame holds no samples
+    bounds: [[usize; 2]; 2],
+    /// Noise standard deviation of the illumination in each pixel
+    deviation: Vec<f64>,
+}
+
+///
+/// Edge-avoiding à-trous wavelet filter (Dammertz et al. 2010) guided by albedo, normal and
+/// depth buffers. Albedo is divided out before filtering so only the noisy illumination
+/// gets blurred and surface colors stay sharp; the color edge-stopping function is scaled
+/// by the estimated noise of each pixel, so converged pixels are left mostly untouched.
+/// Only pixels within `bounds` are filtered and used as neighbours, others are copied.
+///
+pub fn denoise(frame: &Frame, bounds: [[usize; 2]; 2]) -> Vec<Color3> {
+    let [width, height] = frame.size();
+    let mut illumination: Vec<Color3> = frame
+        .color
+        .iter()
+        .zip(&frame.albedo)
+        .map(|(color, albedo)| color / &albedo_divisor(albedo))
+        .collect();
+    let deviation = frame
+        .variance
+        .iter()
+        .zip(&frame.albedo)
+        .map(|(variance, albedo)| variance.sqrt() / luminance(&albedo_divisor(albedo)))
+        .collect();
+    let guide = Guide {
+        width,
+        frame,
+        bounds,
+        deviation,
+    };
+
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let rows_per_thread = (height / threads).max(1);
+    for iteration in 0..ITERATIONS {
+        let step = 1isize << iteration;
+        let mut filtered = vec![Color3::default(); illumination.len()];
+        let input = &illumination;
+        let guide = &guide;
+        thread::scope(|scope| {
+            for (chunk, rows) in filtered.chunks_mut(rows_per_thread * width).enumerate() {
+                scope.spawn(move || {
+                    let first_row = chunk * rows_per_thread;
+                    for (i, dest) in rows.iter_mut().enumerate() {
+                        let x = i % width;
+                        let y = first_row + i / width;
+                        *dest = match guide.contains(x as isize, y as isize) {
+                            true => filter_pixel(guide, input, x, y, step),
+                            false => input[y * width + x],
+                        };
+                    }
+                });
+            }
+        });
+        illumination = filtered;
+    }
+
+    illumination
+        .iter()
+        .zip(&frame.albedo)
+        .map(|(light, albedo)| light * &albedo_divisor(albedo))
+        .collect()
+}
+
+impl Guide<'_> {
+    fn contains(&self, x: isize, y: isize) -> bool {
+        let [min, max] = self.bounds;
+        x >= min[0] as isize && x < max[0] as isize && y >= min[1] as isize && y < max[1] as isize
+    }
+}
+
+fn filter_pixel(guide: &Guide, input: &[Color3], x: usize, y: usize, step: isize) -> Color3 {
+    let frame = guide.frame;
+    let center = y * guide.width + x;
+    let center_luminance = luminance(&input[center]);
+    let color_scale = COLOR_SIGMA * guide.deviation[center] + 1e-4;
+
+    let mut sum = Color3::splat(0.0);
+    let mut weight_sum = 0.0;
+    for (j, kernel_y) in KERNEL.iter().enumerate() {
+        let qy = y as isize + (j as isize - 2) * step;
+        for (i, kernel_x) in KERNEL.iter().enumerate() {
+            let qx = x as isize + (i as isize - 2) * step;
+            if !guide.contains(qx, qy) {
+                continue;
+            }
+            let q = qy as usize * guide.width + qx as usize;
+            // product of the edge-stopping functions evaluated with a single exponent
+            let distance = (center_luminance - luminance(&input[q])).abs() / color_scale +
+                (frame.normal[center] - frame.normal[q]).length_squared() / NORMAL_PHI +
+                (frame.albedo[center] - frame.albedo[q]).length_squared() / ALBEDO_PHI +
+                depth_distance(frame.depth[center], frame.depth[q]);
+            let weight = kernel_x * kernel_y * (-distance).exp();
+            sum = sum + input[q] * weight;
+            weight_sum += weight;
+        }
+    }
+    sum / weight_sum
+}
+
+fn albedo_divisor(albedo: &Color3) -> Color3 {
+    Color3::new(
+        albedo.x().max(MIN_ALBEDO),
+        albedo.y().max(MIN_ALBEDO),
+        albedo.z().max(MIN_ALBEDO),
+    )
+}
+
+///
+/// Depth similarity relative to the distance from the camera, zero depth marks rays which
+/// missed the scene
+///
+fn depth_distance(p: f64, q: f64) -> f64 {
+    let largest = p.max(q);
+    if largest <= 0.0 {
+        return 0.0;
+    }
+    let relative = (p - q).abs() / largest;
+    relative * relative / DEPTH_PHI
+}
@@ -0,0 +1,35 @@
+use crate::renderer::frame::Frame;
+use crate::renderer::pass::RenderPass;
+use egui::ColorImage;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+pub fn save_png(image: &ColorImage, path: &Path) -> std::io::Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(writer, image.size[0] as u32, image.size[1] as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_srgba_unmultiplied())
+        .collect();
+    let mut png_writer = encoder.write_header()?;
+    png_writer.write_image_data(&data)?;
+    Ok(())
+}
+
+///
+/// Save every render pass of the frame as `<prefix>_<pass>.png`, returns written files
+///
+pub fn export_passes(frame: &Frame, prefix: &str) -> std::io::Result<Vec<PathBuf>> {
+    let mut written = vec![];
+    for pass in RenderPass::ALL {
+        let path = PathBuf::from(format!("{}_{}.png", prefix, pass.file_suffix()));
+        save_png(&pass.image(frame), &path)?;
+        written.push(path);
+    }
+    Ok(written)
+}
@@ -0,0 +1,221 @@
+use crate::renderer::filter::Filter;
+use crate::renderer::frame::Frame;
+use crate::renderer::stats::RenderStats;
+use crate::{Color3, Vec3};
+use egui::{Color32, ColorImage};
+
+#[derive(Copy, Clone, Default)]
+struct FilmPixel {
+    weighted_color: Color3,
+    weight: f64,
+}
+
+///
+/// Properties of the first surface seen by a camera ray, used to guide the denoiser and
+/// for the auxiliary render passes. Rays that miss the scene keep zero normal and depth.
+///
+#[derive(Copy, Clone, Default)]
+pub struct SampleFeatures {
+    pub albedo: Color3,
+    /// Outward normal of the surface in world space
+    pub normal: Vec3,
+    pub depth: f64,
+    pub uv: [f64; 2],
+    pub object: Option<usize>,
+    /// Number of surfaces hit along the whole path
+    pub hits: u32,
+}
+
+#[derive(Copy, Clone, Default)]
+struct FeatureSums {
+    albedo: Color3,
+    normal: Vec3,
+    depth: f64,
+    uv: [f64; 2],
+    hits: u32,
+    /// Object seen by the first sample of the pixel
+    object: Option<usize>,
+}
+
+///
+/// Running mean and variance of the luminance of samples generated for a pixel
+/// (Welford's online algorithm)
+///
+#[derive(Copy, Clone, Default)]
+struct PixelStatistics {
+    samples: u32,
+    mean: f64,
+    m2: f64,
+}
+
+impl PixelStatistics {
+    fn add(&mut self, value: f64) {
+        self.samples += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.samples as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    ///
+    /// Standard error of the pixel mean relative to the mean itself, dark pixels are
+    /// compared against a small floor to avoid dividing by zero
+    ///
+    fn relative_error(&self) -> f64 {
+        if self.samples < 2 {
+            return f64::INFINITY;
+        }
+        self.mean_variance().sqrt() / self.mean.max(MIN_LUMINANCE)
+    }
+
+    /// Estimated variance of the pixel mean
+    fn mean_variance(&self) -> f64 {
+        if self.samples < 2 {
+            return 0.0;
+        }
+        self.m2 / (self.samples - 1) as f64 / self.samples as f64
+    }
+}
+
+///
+/// Accumulation buffer for image samples. Each sample is splatted into every pixel
+/// covered by the reconstruction filter, weighted by the filter value at that pixel.
+/// Rows are stored bottom-up, the same way the camera casts rays.
+///
+pub struct Film {
+    size: [usize; 2],
+    filter: Filter,
+    pixels: Vec<FilmPixel>,
+    statistics: Vec<PixelStatistics>,
+    features: Vec<FeatureSums>,
+}
+
+impl Film {
+    pub fn new(size: [usize; 2], filter: Filter) -> Self {
+        Self {
+            size,
+            filter,
+            pixels: vec![FilmPixel::default(); size[0] * size[1]],
+            statistics: vec![PixelStatistics::default(); size[0] * size[1]],
+            features: vec![FeatureSums::default(); size[0] * size[1]],
+        }
+    }
+
+    ///
+    /// Add sample generated for `pixel` at raster `position`, pixel (x, y) covers
+    /// [x, x+1) x [y, y+1). Features are averaged only over samples of their own pixel.
+    ///
+    pub fn add_sample(
+        &mut self,
+        pixel: [usize; 2],
+        position: [f64; 2],
+        color: Color3,
+        features: &SampleFeatures,
+    ) {
+        let index = pixel[1] * self.size[0] + pixel[0];
+        self.statistics[index].add(luminance(&color));
+        let sums = &mut self.features[index];
+        sums.albedo = sums.albedo + features.albedo;
+        sums.normal = sums.normal + features.normal;
+        sums.depth += features.depth;
+        sums.uv = [sums.uv[0] + features.uv[0], sums.uv[1] + features.uv[1]];
+        sums.hits += features.hits;
+        if self.statistics[index].samples == 1 {
+            sums.object = features.object;
+        }
+
+        let radius = self.filter.radius;
+        let x0 = (position[0] - 0.5 - radius).ceil().max(0.0) as usize;
+        let y0 = (position[1] - 0.5 - radius).ceil().max(0.0) as usize;
+        let x1 = (position[0] - 0.5 + radius).floor();
+        let y1 = (position[1] - 0.5 + radius).floor();
+        if x1 < 0.0 || y1 < 0.0 {
+            return;
+        }
+        let x1 = (x1 as usize).min(self.size[0] - 1);
+        let y1 = (y1 as usize).min(self.size[1] - 1);
+
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let weight = self
+                    .filter
+                    .evaluate(x as f64 + 0.5 - position[0], y as f64 + 0.5 - position[1]);
+                if weight == 0.0 {
+                    continue;
+                }
+                let pixel = &mut self.pixels[y * self.size[0] + x];
+                pixel.weighted_color = pixel.weighted_color + color * weight;
+                pixel.weight += weight;
+            }
+        }
+    }
+
+    pub fn color(&self, x: usize, y: usize) -> Color3 {
+        let pixel = &self.pixels[y * self.size[0] + x];
+        if pixel.weight <= 0.0 {
+            return Color3::splat(0.0);
+        }
+        pixel.weighted_color / pixel.weight
+    }
+
+    ///
+    /// True if the estimated error of the pixel dropped below `threshold`
+    ///
+    pub fn converged(&self, x: usize, y: usize, threshold: f64) -> bool {
+        self.statistics[y * self.size[0] + x].relative_error() < threshold
+    }
+
+    pub fn frame(&self) -> Frame {
+        let pixel_count = self.size[0] * self.size[1];
+        let mut color = Vec::with_capacity(pixel_count);
+        let mut albedo = Vec::with_capacity(pixel_count);
+        let mut normal = Vec::with_capacity(pixel_count);
+        let mut depth = Vec::with_capacity(pixel_count);
+        let mut uv = Vec::with_capacity(pixel_count);
+        let mut variance = Vec::with_capacity(pixel_count);
+        let mut object_id = Vec::with_capacity(pixel_count);
+        let mut hit_count = Vec::with_capacity(pixel_count);
+        let mut sample_counts = Vec::with_capacity(pixel_count);
+
+        for y in (0..self.size[1]).rev() {
+            for x in 0..self.size[0] {
+                let index = y * self.size[0] + x;
+                let statistics = &self.statistics[index];
+                let samples = statistics.samples;
+                let sums = &self.features[index];
+                let scale = 1.0 / samples.max(1) as f64;
+
+                color.push(self.color(x, y));
+                albedo.push(sums.albedo * scale);
+                normal.push(sums.normal * scale);
+                depth.push(sums.depth * scale);
+                uv.push(sums.uv.map(|c| c * scale));
+                variance.push(statistics.mean_variance());
+                object_id.push(sums.object);
+                hit_count.push(sums.hits as f64 * scale);
+                sample_counts.push(samples);
+            }
+        }
+        let mut frame = Frame {
+            image: ColorImage::new(self.size, Color32::BLACK),
+            color,
+            albedo,
+            normal,
+            depth,
+            uv,
+            variance,
+            object_id,
+            hit_count,
+            sample_counts,
+            region: None,
+            stats: RenderStats::default(),
+        };
+        frame.update_image();
+        frame
+    }
+}
+
+const MIN_LUMINANCE: f64 = 0.01;
+
+pub(crate) fn luminance(color: &Color3) -> f64 {
+    0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z()
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::fmt::{Display, Formatter};
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum FilterKind {
+    Box,
+    Tent,
+    Gaussian,
+    MitchellNetravali,
+    Lanczos,
+}
+
+impl FilterKind {
+    pub const ALL: [FilterKind; 5] = [
+        FilterKind::Box,
+        FilterKind::Tent,
+        FilterKind::Gaussian,
+        FilterKind::MitchellNetravali,
+        FilterKind::Lanczos,
+    ];
+}
+
+impl Display for FilterKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            FilterKind::Box => "Box",
+            FilterKind::Tent => "Tent",
+            FilterKind::Gaussian => "Gaussian",
+            FilterKind::MitchellNetravali => "Mitchell-Netravali",
+            FilterKind::Lanczos => "Lanczos",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+///
+/// Pixel reconstruction filter, weights samples by their distance (in pixels) from the
+/// pixel center. Filters are separable and zero outside of `radius`.
+///
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Filter {
+    pub kind: FilterKind,
+    pub radius: f64,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            kind: FilterKind::Box,
+            radius: 0.5,
+        }
+    }
+}
+
+impl Filter {
+    pub fn evaluate(&self, dx: f64, dy: f64) -> f64 {
+        self.evaluate_1d(dx) * self.evaluate_1d(dy)
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        let x = x.abs();
+        let radius = self.radius;
+        if x > radius {
+            return 0.0;
+        }
+
+        match self.kind {
+            FilterKind::Box => 1.0,
+            FilterKind::Tent => radius - x,
+            FilterKind::Gaussian => {
+                let sigma = radius / 3.0;
+                let gaussian = |x: f64| (-x * x / (2.0 * sigma * sigma)).exp();
+                (gaussian(x) - gaussian(radius)).max(0.0)
+            }
+            FilterKind::MitchellNetravali => mitchell(2.0 * x / radius, 1.0 / 3.0, 1.0 / 3.0),
+            FilterKind::Lanczos => sinc(x) * sinc(x / radius),
+        }
+    }
+}
+
+fn mitchell(x: f64, b: f64, c: f64) -> f64 {
+    let x = x.abs();
+    if x > 1.0 {
+        ((-b - 6.0 * c) * x * x * x +
+            (6.0 * b + 30.0 * c) * x * x +
+            (-12.0 * b - 48.0 * c) * x +
+            (8.0 * b + 24.0 * c)) /
+            6.0
+    } else {
+        ((12.0 - 9.0 * b - 6.0 * c) * x * x * x +
+            (-18.0 + 12.0 * b + 6.0 * c) * x * x +
+            (6.0 - 2.0 * b)) /
+            6.0
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-5 {
+        return 1.0;
+    }
+    (PI * x).sin() / (PI * x)
+}
@@ -0,0 +1,95 @@
+use crate::renderer::stats::RenderStats;
+use crate::{Color3, Vec3};
+use egui::{Color32, ColorImage};
+
+///
+/// Result of the render sent back to the UI. Buffers hold linear values and are stored
+/// top-down like the image, `image` is the gamma corrected color buffer.
+///
+#[derive(Clone)]
+pub struct Frame {
+    pub image: ColorImage,
+    pub color: Vec<Color3>,
+    pub albedo: Vec<Color3>,
+    pub normal: Vec<Vec3>,
+    pub depth: Vec<f64>,
+    /// Surface coordinates of the first hit
+    pub uv: Vec<[f64; 2]>,
+    /// Variance of the mean luminance of each pixel
+    pub variance: Vec<f64>,
+    /// Index of the scene object seen through the pixel
+    pub object_id: Vec<Option<usize>>,
+    /// Average number of surfaces hit by paths of the pixel
+    pub hit_count: Vec<f64>,
+    pub sample_counts: Vec<u32>,
+    /// Pixels `[min, max)` rendered when only a region of the image was requested
+    pub region: Option<[[usize; 2]; 2]>,
+    pub stats: RenderStats,
+}
+
+impl Frame {
+    pub fn size(&self) -> [usize; 2] {
+        self.image.size
+    }
+
+    pub fn max_sample_count(&self) -> u32 {
+        self.sample_counts.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn set_color(&mut self, color: Vec<Color3>) {
+        self.color = color;
+        self.update_image();
+    }
+
+    ///
+    /// Copy the rendered region of `region_frame` over this frame of the same size
+    ///
+    pub fn composite(&mut self, region_frame: &Frame) {
+        let [min, max] = match region_frame.region {
+            Some(region) => region,
+            None => [[0, 0], region_frame.size()],
+        };
+        let width = self.size()[0];
+        for y in min[1]..max[1] {
+            let row = y * width + min[0]..y * width + max[0];
+            self.image.pixels[row.clone()].copy_from_slice(&region_frame.image.pixels[row.clone()]);
+            self.color[row.clone()].copy_from_slice(&region_frame.color[row.clone()]);
+            self.albedo[row.clone()].copy_from_slice(&region_frame.albedo[row.clone()]);
+            self.normal[row.clone()].copy_from_slice(&region_frame.normal[row.clone()]);
+            self.depth[row.clone()].copy_from_slice(&region_frame.depth[row.clone()]);
+            self.uv[row.clone()].copy_from_slice(&region_frame.uv[row.clone()]);
+            self.variance[row.clone()].copy_from_slice(&region_frame.variance[row.clone()]);
+            self.object_id[row.clone()].copy_from_slice(&region_frame.object_id[row.clone()]);
+            self.hit_count[row.clone()].copy_from_slice(&region_frame.hit_count[row.clone()]);
+            self.sample_counts[row.clone()].copy_from_slice(&region_frame.sample_counts[row]);
+        }
+    }
+
+    pub(crate) fn update_image(&mut self) {
+        for (dest, color) in self.image.pixels.iter_mut().zip(&self.color) {
+            *dest = to_color32(color);
+        }
+    }
+}
+
+///
+/// Gamma corrected (gamma 2) conversion of linear color
+///
+pub fn to_color32(color: &Color3) -> Color32 {
+    let r = color.x().max(0.0).sqrt().clamp(0.0, 1.0);
+    let g = color.y().max(0.0).sqrt().clamp(0.0, 1.0);
+    let b = color.z().max(0.0).sqrt().clamp(0.0, 1.0);
+
+    Color32::from_rgb(
+        fast_round(r * ALMOST_256),
+        fast_round(g * ALMOST_256),
+        fast_round(b * ALMOST_256),
+    )
+}
+
+const ALMOST_256: f64 = 255.999;
+
+#[inline]
+fn fast_round(r: f64) -> u8 {
+    (r + 0.5).floor() as _ // rust does a saturating cast since 1.45
+}
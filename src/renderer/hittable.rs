@@ -1,4 +1,6 @@
 use crate::math::Point3;
+use crate::renderer::material::Material;
+use crate::renderer::sampler::Sampler;
 use crate::{Color3, Ray, Vec3};
 
 pub struct Hit {
@@ -6,10 +8,57 @@ pub struct Hit {
     pub(crate) normal: Vec3,
     pub(crate) t: f64,
     pub front_face: bool,
+    /// Surface coordinates in [0, 1]²
+    pub uv: [f64; 2],
     pub surface: Color3,
+    pub material: Material,
+    /// Light emitted towards the ray origin
+    pub emission: Color3,
+    /// Index of the scene object that was hit
+    pub(crate) object: usize,
+}
+
+impl Hit {
+    ///
+    /// Hit of a diffuse surface at parameter `t` of the ray, the normal is turned to face
+    /// the ray and `front_face` tells whether the ray hit the outside
+    ///
+    pub fn diffuse(ray: &Ray, t: f64, outward_normal: Vec3, uv: [f64; 2], color: Color3) -> Self {
+        let front_face = Vec3::dot(ray.direction(), &outward_normal) < 0.0;
+        let normal = match front_face {
+            true => outward_normal,
+            false => -outward_normal,
+        };
+        Hit {
+            point: ray.at(t),
+            normal,
+            t,
+            front_face,
+            uv,
+            surface: color,
+            material: Material::Lambertian,
+            emission: Color3::splat(0.0),
+            object: 0,
+        }
+    }
+
+    /// Normal pointing out of the object regardless of the side the ray came from
+    pub fn outward_normal(&self) -> Vec3 {
+        match self.front_face {
+            true => self.normal,
+            false => -self.normal,
+        }
+    }
 }
 
 pub trait Hittable: Send + Sync + Clone {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit>;
+    /// Closest hit between `t_min` and `t_max`, volumes draw their scattering from `sampler`
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<Hit>;
+    ///
+    /// Every point where the ray (extended in both directions) crosses the object's
+    /// surface sorted by distance, `front_face` tells entries from exits. Objects without
+    /// a closed surface, like volumes, report none.
+    ///
+    fn intersections(&self, ray: &Ray) -> Vec<Hit>;
     fn name(&self) -> String;
 }
@@ -0,0 +1,67 @@
+use crate::renderer::hittable::Hit;
+use crate::renderer::sampler::uniform_sphere;
+use crate::{Color3, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+///
+/// How light scatters at a hit point, the color comes from [`Hit::surface`]
+///
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Material {
+    /// Diffuse surface
+    Lambertian,
+    /// Scatters uniformly in all directions, used inside participating media
+    Isotropic,
+    /// Henyey–Greenstein phase function with the given anisotropy in (-1, 1), positive
+    /// values scatter forward
+    HenyeyGreenstein(f64),
+}
+
+impl Material {
+    ///
+    /// Direction of the scattered ray and the attenuation of the light it brings back for
+    /// a ray arriving along `direction`, `u` is a sample in [0, 1)²
+    ///
+    pub fn scatter(&self, direction: &Vec3, hit: &Hit, u: [f64; 2]) -> (Vec3, Color3) {
+        match self {
+            Material::Lambertian => {
+                let direction = hit.normal + uniform_sphere(u);
+                // sphere sample opposite to the normal gives degenerate direction
+                let direction = match direction.length_squared() < 1e-12 {
+                    true => hit.normal,
+                    false => direction,
+                };
+                (direction, hit.surface * 0.5)
+            }
+            Material::Isotropic => (uniform_sphere(u), hit.surface),
+            Material::HenyeyGreenstein(g) => (
+                henyey_greenstein(&direction.normalized(), *g, u),
+                hit.surface,
+            ),
+        }
+    }
+}
+
+///
+/// Sample a direction around `forward` (unit length) distributed by the Henyey–Greenstein
+/// phase function
+///
+fn henyey_greenstein(forward: &Vec3, g: f64, u: [f64; 2]) -> Vec3 {
+    let cos_theta = if g.abs() < 1e-3 {
+        1.0 - 2.0 * u[0]
+    } else {
+        let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * u[0]);
+        ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+    };
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * PI * u[1];
+
+    let helper = match forward.x().abs() > 0.9 {
+        true => Vec3::new(0.0, 1.0, 0.0),
+        false => Vec3::new(1.0, 0.0, 0.0),
+    };
+    let tangent = Vec3::cross(forward, &helper).normalized();
+    let bitangent = Vec3::cross(forward, &tangent);
+    tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + *forward * cos_theta
+}
@@ -1,28 +1,68 @@
 use crate::{Color3, Ray, Vec3};
-use egui::{Color32, ColorImage};
 use std::fmt::{Display, Formatter};
 use std::ops::Mul;
 
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
+use crate::renderer::camera::{CameraView, Projection};
+use crate::renderer::filter::Filter;
+use crate::renderer::frame::Frame;
+use crate::renderer::region::Region;
 use crate::renderer::resolution::Resolution;
+use crate::renderer::sampler::SamplerKind;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{channel, Receiver, RecvError, Sender};
+use std::sync::Arc;
 
 use crate::renderer::scene::Scene;
 use crate::renderer::thread::{RenderThread, RenderThreadCommand, RenderThreadResponse};
 
+pub mod animation;
+pub mod bvh;
 pub mod camera;
+mod denoiser;
+pub mod export;
+mod film;
+pub mod filter;
+pub mod frame;
 pub mod hittable;
-mod resolution;
+pub mod material;
+pub mod pass;
+pub mod region;
+pub mod resolution;
+pub mod sampler;
 pub mod scene;
+pub mod stats;
 mod thread;
 
-#[derive(Clone)]
+/// Missing fields (e.g. in state saved by an older version) get their default values
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RenderParams {
+    pub camera: CameraView,
+    pub projection: Projection,
     pub(crate) focal_length: f64,
+    pub(crate) aperture: f64,
+    pub(crate) focus_distance: f64,
+    /// Time interval in which the camera records, objects moving in it are blurred
+    pub shutter_open: f64,
+    pub shutter_close: f64,
     pub(crate) samples: i16,
+    pub sampler: SamplerKind,
+    pub filter: Filter,
+    pub adaptive_sampling: bool,
+    pub(crate) min_samples: i16,
+    pub adaptive_threshold: f64,
+    pub denoise: bool,
     pub min_ray_distance: f64,
+    /// Size of the final image
     pub resolution: Resolution,
+    /// Fraction of `resolution` actually rendered, for quick test renders
+    pub render_scale: f64,
+    /// Render only this part of the image, it is composited into the previous render
+    pub region: Option<Region>,
+    #[serde(skip)]
     pub available_resolutions: Vec<Resolution>,
 }
 
@@ -30,32 +70,79 @@ impl Default for RenderParams {
     fn default() -> Self {
         let resolutions = Resolution::available();
         Self {
+            camera: CameraView::default(),
+            projection: Projection::Perspective,
             focal_length: 1.0,
+            aperture: 0.0,
+            focus_distance: 1.0,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
             samples: 100,
+            sampler: SamplerKind::Sobol,
+            filter: Filter::default(),
+            adaptive_sampling: false,
+            min_samples: 16,
+            adaptive_threshold: 0.02,
+            denoise: false,
             min_ray_distance: 0.001,
             resolution: resolutions[0],
+            render_scale: 1.0,
+            region: None,
             available_resolutions: resolutions,
         }
     }
 }
 
+/// Fraction of the resolution used for previews while the camera moves
+const PREVIEW_SCALE: f64 = 0.25;
+const PREVIEW_SAMPLES: i16 = 2;
+
+impl RenderParams {
+    /// Resolution of the rendered image after applying the render scale
+    pub fn render_resolution(&self) -> Resolution {
+        self.resolution * self.render_scale
+    }
+
+    ///
+    /// Cheap version of these parameters for interactive feedback: reduced resolution and
+    /// sample count, no adaptive sampling or denoising
+    ///
+    pub fn preview(&self) -> RenderParams {
+        RenderParams {
+            render_scale: self.render_scale * PREVIEW_SCALE,
+            samples: self.samples.min(PREVIEW_SAMPLES),
+            adaptive_sampling: false,
+            denoise: false,
+            region: None,
+            ..self.clone()
+        }
+    }
+}
+
 pub struct Renderer {
     sender: Sender<RenderThreadCommand>,
     receiver: Receiver<RenderThreadResponse>,
+    /// Id of the most recent frame request, renders of older requests are abandoned
+    latest_request: Arc<AtomicU64>,
     pub(crate) waiting_for_next_frame: bool,
     pub(crate) render_requested: bool,
     pub(crate) progress: f64,
+    /// Scene snapshot last sent to the render thread
+    scene: Option<Arc<Scene>>,
 }
 
 impl Renderer {
     pub(crate) fn create() -> Self {
         let (command_sender, command_revceiver) = channel();
         let (response_sender, response_receiver) = channel();
+        let latest_request = Arc::new(AtomicU64::new(0));
 
+        let thread_latest_request = latest_request.clone();
         let handle = std::thread::spawn(|| {
             let mut thread = RenderThread {
                 sender: response_sender,
                 receiver: command_revceiver,
+                latest_request: thread_latest_request,
                 scene: None,
                 params: RenderParams::default(),
             };
@@ -66,9 +153,11 @@ impl Renderer {
         Self {
             sender: command_sender,
             receiver: response_receiver,
+            latest_request,
             waiting_for_next_frame: false,
             render_requested: false,
             progress: 0.0,
+            scene: None,
         }
     }
 
@@ -79,38 +168,69 @@ impl Renderer {
     }
 
     ///
-    /// Try to update output image, return true if image changed
-    ///
-    /// # Arguments
-    ///
-    /// * `image`: Image reference to update
-    ///
-    /// returns: bool
+    /// Process messages from the render thread, return the frame if a new one was rendered
     ///
-    pub fn update_result(&mut self, image: &mut ColorImage) -> bool {
+    pub fn update_result(&mut self) -> Option<Frame> {
+        let mut result = None;
+        let latest_request = self.latest_request.load(Ordering::SeqCst);
         while let Ok(f) = self.receiver.try_recv() {
-            return match f {
-                RenderThreadResponse::FrameRendered(im) => {
-                    *image = im;
+            match f {
+                // responses of abandoned renders can still be queued
+                RenderThreadResponse::FrameRendered(request, _) |
+                RenderThreadResponse::ProgressiveFrame(request, _) |
+                RenderThreadResponse::ProgressUpdate(request, _)
+                    if request != latest_request => {}
+                RenderThreadResponse::FrameRendered(_, frame) => {
+                    result = Some(frame);
                     self.waiting_for_next_frame = false;
                     self.render_requested = false;
-                    true
                 }
-                RenderThreadResponse::ProgressUpdate(fraction) => {
+                RenderThreadResponse::ProgressiveFrame(_, frame) => {
+                    result = Some(frame);
+                }
+                RenderThreadResponse::ProgressUpdate(_, fraction) => {
                     self.progress = fraction;
-                    false
                 }
             }
         }
-        return false;
+        result
     }
 
+    ///
+    /// Start rendering a frame, a render that is still in progress is cancelled
+    ///
     pub fn request_render(&mut self, params: RenderParams, scene: &Scene) {
-        if !self.waiting_for_next_frame {
-            self.send_command(RenderThreadCommand::UpdateScene(scene.clone()));
-            self.send_command(RenderThreadCommand::UpdateRenderParams(params));
-            self.send_command(RenderThreadCommand::RequestFrame);
-            self.waiting_for_next_frame = true
-        } else {}
+        let request = self.latest_request.fetch_add(1, Ordering::SeqCst) + 1;
+        // a snapshot only copies the list of nodes, the objects are shared until the UI
+        // edits them
+        if self.scene.as_deref() != Some(scene) {
+            let snapshot = Arc::new(scene.clone());
+            self.send_command(RenderThreadCommand::UpdateScene(snapshot.clone()));
+            self.scene = Some(snapshot);
+        }
+        self.send_command(RenderThreadCommand::UpdateRenderParams(params));
+        self.send_command(RenderThreadCommand::RequestFrame(request));
+        self.waiting_for_next_frame = true;
+        self.progress = 0.0;
+    }
+
+    ///
+    /// Render a frame and wait until it's finished, for rendering without the UI
+    ///
+    pub fn render_blocking(&mut self, params: RenderParams, scene: &Scene) -> Frame {
+        self.request_render(params, scene);
+        let request = self.latest_request.load(Ordering::SeqCst);
+        loop {
+            let response = self
+                .receiver
+                .recv()
+                .expect("Unable to comunicate with renderer");
+            if let RenderThreadResponse::FrameRendered(id, frame) = response {
+                if id == request {
+                    self.waiting_for_next_frame = false;
+                    return frame;
+                }
+            }
+        }
     }
 }
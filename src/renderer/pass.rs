@@ -0,0 +1,145 @@
+use crate::renderer::frame::{to_color32, Frame};
+use egui::{Color32, ColorImage};
+use std::fmt::{Display, Formatter};
+
+///
+/// Buffers of the rendered frame that can be displayed or exported as images
+///
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RenderPass {
+    Beauty,
+    Depth,
+    Normal,
+    Uv,
+    Albedo,
+    ObjectId,
+    HitCount,
+}
+
+impl RenderPass {
+    pub const ALL: [RenderPass; 7] = [
+        RenderPass::Beauty,
+        RenderPass::Depth,
+        RenderPass::Normal,
+        RenderPass::Uv,
+        RenderPass::Albedo,
+        RenderPass::ObjectId,
+        RenderPass::HitCount,
+    ];
+
+    /// Name used in exported file names
+    pub fn file_suffix(&self) -> &'static str {
+        match self {
+            RenderPass::Beauty => "beauty",
+            RenderPass::Depth => "depth",
+            RenderPass::Normal => "normal",
+            RenderPass::Uv => "uv",
+            RenderPass::Albedo => "albedo",
+            RenderPass::ObjectId => "object_id",
+            RenderPass::HitCount => "hit_count",
+        }
+    }
+
+    pub fn image(&self, frame: &Frame) -> ColorImage {
+        let pixels = match self {
+            RenderPass::Beauty => return frame.image.clone(),
+            RenderPass::Depth => {
+                // closest surfaces are white, sky is black
+                let max_depth = frame.depth.iter().copied().fold(0.0, f64::max);
+                frame
+                    .depth
+                    .iter()
+                    .map(|depth| match *depth > 0.0 {
+                        true => Color32::from_gray((255.0 * (1.0 - depth / max_depth)) as u8),
+                        false => Color32::BLACK,
+                    })
+                    .collect()
+            }
+            RenderPass::Normal => frame
+                .normal
+                .iter()
+                .map(|normal| match normal.length_squared() > 0.0 {
+                    true => {
+                        let [r, g, b] = normal
+                            .normalized()
+                            .data
+                            .map(|c| ((c + 1.0) * 0.5 * 255.0) as u8);
+                        Color32::from_rgb(r, g, b)
+                    }
+                    false => Color32::BLACK,
+                })
+                .collect(),
+            RenderPass::Uv => frame
+                .uv
+                .iter()
+                .map(|[u, v]| Color32::from_rgb((u * 255.0) as u8, (v * 255.0) as u8, 0))
+                .collect(),
+            RenderPass::Albedo => frame.albedo.iter().map(to_color32).collect(),
+            RenderPass::ObjectId => frame
+                .object_id
+                .iter()
+                .map(|object| match object {
+                    Some(index) => object_color(*index),
+                    None => Color32::BLACK,
+                })
+                .collect(),
+            RenderPass::HitCount => {
+                let max_hits = frame.hit_count.iter().copied().fold(0.0, f64::max);
+                frame
+                    .hit_count
+                    .iter()
+                    .map(|hits| heat_color((hits / max_hits.max(1.0)) as f32))
+                    .collect()
+            }
+        };
+        ColorImage {
+            size: frame.size(),
+            pixels,
+        }
+    }
+}
+
+impl Display for RenderPass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            RenderPass::Beauty => "Beauty",
+            RenderPass::Depth => "Depth",
+            RenderPass::Normal => "World normals",
+            RenderPass::Uv => "Surface UV",
+            RenderPass::Albedo => "Albedo",
+            RenderPass::ObjectId => "Object ID",
+            RenderPass::HitCount => "Hit count",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+///
+/// Distinct, stable color for each object index
+///
+pub fn object_color(index: usize) -> Color32 {
+    // golden ratio hue steps keep neighbouring indices apart
+    let hue = (index as f32 * 0.618_034).fract();
+    egui::color::Hsva::new(hue, 0.7, 0.9, 1.0).into()
+}
+
+///
+/// Maps value in [0, 1] from blue (low) to red (high)
+///
+pub fn heat_color(t: f32) -> Color32 {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0],
+        [0.0, 1.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [1.0, 0.0, 0.0],
+    ];
+    let position = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let index = (position as usize).min(STOPS.len() - 2);
+    let alpha = position - index as f32;
+    let [r, g, b] = [0, 1, 2].map(|c| {
+        let value = STOPS[index][c] * (1.0 - alpha) + STOPS[index + 1][c] * alpha;
+        (value * 255.0) as u8
+    });
+    Color32::from_rgb(r, g, b)
+}
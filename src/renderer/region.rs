@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+///
+/// Part of the image to render, in fractions of the image size measured from the top left
+/// corner, so it stays over the same content when the resolution changes
+///
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Region {
+    pub min: [f64; 2],
+    pub max: [f64; 2],
+}
+
+impl Region {
+    /// Region spanned by two opposite corners given in any order
+    pub fn new(a: [f64; 2], b: [f64; 2]) -> Self {
+        let clamp = |value: f64| value.clamp(0.0, 1.0);
+        Self {
+            min: [clamp(a[0].min(b[0])), clamp(a[1].min(b[1]))],
+            max: [clamp(a[0].max(b[0])), clamp(a[1].max(b[1]))],
+        }
+    }
+
+    ///
+    /// Pixels `[min, max)` covered by the region in an image of the given size, always at
+    /// least one pixel
+    ///
+    pub fn pixels(&self, size: [usize; 2]) -> [[usize; 2]; 2] {
+        let mut bounds = [[0; 2]; 2];
+        for axis in 0..2 {
+            let min = ((self.min[axis] * size[axis] as f64) as usize).min(size[axis] - 1);
+            let max = ((self.max[axis] * size[axis] as f64).ceil() as usize).min(size[axis]);
+            bounds[0][axis] = min;
+            bounds[1][axis] = max.max(min + 1);
+        }
+        bounds
+    }
+}
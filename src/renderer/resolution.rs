@@ -1,15 +1,53 @@
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::ops::Mul;
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Resolution {
     x: i32,
     y: i32,
 }
 
+/// Smallest and largest allowed image side, in pixels
+pub const MIN_SIZE: i32 = 16;
+pub const MAX_SIZE: i32 = 16384;
+
 impl Resolution {
-    fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
+    ///
+    /// Resolution clamped to the supported range and rounded up to even sizes, which video
+    /// encoders expect when rendered frames are turned into a movie
+    ///
+    pub fn new(x: i32, y: i32) -> Self {
+        let valid = |size: i32| {
+            let size = size.clamp(MIN_SIZE, MAX_SIZE);
+            size + size % 2
+        };
+        Self {
+            x: valid(x),
+            y: valid(y),
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.x
+    }
+
+    pub fn height(&self) -> i32 {
+        self.y
+    }
+
+    pub fn aspect_ratio(&self) -> f64 {
+        self.x as f64 / self.y as f64
+    }
+
+    /// Resolution with the given width and height computed from the aspect ratio
+    pub fn from_width(width: i32, aspect_ratio: f64) -> Self {
+        Self::new(width, (width as f64 / aspect_ratio).round() as i32)
+    }
+
+    /// Resolution with the given height and width computed from the aspect ratio
+    pub fn from_height(height: i32, aspect_ratio: f64) -> Self {
+        Self::new((height as f64 * aspect_ratio).round() as i32, height)
     }
 
     pub(crate) fn available() -> Vec<Resolution> {
@@ -24,11 +62,59 @@ impl Resolution {
     }
 }
 
+///
+/// Common image proportions offered in the resolution editor
+///
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum AspectRatio {
+    Square,
+    Standard,
+    Wide,
+    UltraWide,
+    Anamorphic,
+}
+
+impl AspectRatio {
+    pub const ALL: [AspectRatio; 5] = [
+        AspectRatio::Square,
+        AspectRatio::Standard,
+        AspectRatio::Wide,
+        AspectRatio::UltraWide,
+        AspectRatio::Anamorphic,
+    ];
+
+    pub fn ratio(&self) -> f64 {
+        match self {
+            AspectRatio::Square => 1.0,
+            AspectRatio::Standard => 4.0 / 3.0,
+            AspectRatio::Wide => 16.0 / 9.0,
+            AspectRatio::UltraWide => 21.0 / 9.0,
+            AspectRatio::Anamorphic => 2.39,
+        }
+    }
+}
+
+impl Display for AspectRatio {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AspectRatio::Square => "1:1",
+            AspectRatio::Standard => "4:3",
+            AspectRatio::Wide => "16:9",
+            AspectRatio::UltraWide => "21:9",
+            AspectRatio::Anamorphic => "2.39:1",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 impl Mul<f64> for Resolution {
     type Output = Resolution;
 
     fn mul(self, rhs: f64) -> Self::Output {
-        Self::new((self.x as f64 * rhs) as i32, (self.y as f64 * rhs) as i32)
+        Self::new(
+            (self.x as f64 * rhs).round() as i32,
+            (self.y as f64 * rhs).round() as i32,
+        )
     }
 }
 
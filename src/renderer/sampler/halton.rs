@@ -0,0 +1,85 @@
+use crate::renderer::sampler::{hash_combine, pixel_seed, to_unit_float, Sampler};
+use rand::rngs::ThreadRng;
+use rand::{thread_rng, Rng};
+
+/// Number of dimensions backed by the Halton sequence, further dimensions are pure random
+const MAX_DIMENSIONS: usize = 128;
+
+///
+/// Halton sequence with a consecutive prime base for every dimension. Pixels share the
+/// sequence but each one gets its own random toroidal shift (Cranley-Patterson rotation).
+///
+pub struct HaltonSampler {
+    primes: Vec<u32>,
+    rng: ThreadRng,
+    pixel_seed: u32,
+    index: u32,
+    dimension: usize,
+}
+
+impl HaltonSampler {
+    pub fn new() -> Self {
+        Self {
+            primes: first_primes(MAX_DIMENSIONS),
+            rng: thread_rng(),
+            pixel_seed: 0,
+            index: 0,
+            dimension: 0,
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_sample(&mut self, pixel: [usize; 2], index: u32) {
+        self.pixel_seed = pixel_seed(pixel);
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        let dimension = self.dimension;
+        self.dimension += 1;
+        if dimension >= self.primes.len() {
+            return self.rng.gen::<f64>();
+        }
+
+        let shift = to_unit_float(hash_combine(self.pixel_seed, dimension as u32));
+        let value = radical_inverse(self.primes[dimension], self.index) + shift;
+        if value >= 1.0 {
+            value - 1.0
+        } else {
+            value
+        }
+    }
+
+    fn next_2d(&mut self) -> [f64; 2] {
+        [self.next_1d(), self.next_1d()]
+    }
+}
+
+fn radical_inverse(base: u32, mut index: u32) -> f64 {
+    let base = base as u64;
+    let inv_base = 1.0 / base as f64;
+    let mut inv_base_n = 1.0;
+    let mut reversed: u64 = 0;
+    while index > 0 {
+        let next = index as u64 / base;
+        let digit = index as u64 - next * base;
+        reversed = reversed * base + digit;
+        inv_base_n *= inv_base;
+        index = next as u32;
+    }
+    (reversed as f64 * inv_base_n).min(1.0 - f64::EPSILON)
+}
+
+fn first_primes(count: usize) -> Vec<u32> {
+    let mut primes: Vec<u32> = Vec::with_capacity(count);
+    let mut candidate = 2;
+    while primes.len() < count {
+        if primes.iter().all(|p| candidate % p != 0) {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+    primes
+}
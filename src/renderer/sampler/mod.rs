@@ -0,0 +1,122 @@
+use std::fmt::{Display, Formatter};
+
+use crate::renderer::sampler::halton::HaltonSampler;
+use crate::renderer::sampler::sobol::SobolSampler;
+use crate::renderer::sampler::stratified::StratifiedSampler;
+use crate::renderer::sampler::uniform::UniformSampler;
+use crate::Vec3;
+use serde::{Deserialize, Serialize};
+
+mod halton;
+mod sobol;
+mod stratified;
+mod uniform;
+
+///
+/// Source of sample values in [0, 1) used for pixel positions, lens positions and bounce
+/// directions. Every sample of every pixel consumes dimensions in the same order, so
+/// low-discrepancy samplers can keep the dimensions decorrelated from each other.
+///
+pub trait Sampler {
+    /// Start generating values for sample `index` of the given pixel
+    fn start_sample(&mut self, pixel: [usize; 2], index: u32);
+    fn next_1d(&mut self) -> f64;
+    fn next_2d(&mut self) -> [f64; 2];
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum SamplerKind {
+    Uniform,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+impl SamplerKind {
+    pub const ALL: [SamplerKind; 4] = [
+        SamplerKind::Uniform,
+        SamplerKind::Stratified,
+        SamplerKind::Halton,
+        SamplerKind::Sobol,
+    ];
+
+    pub fn create(&self, samples_per_pixel: u32) -> Box<dyn Sampler> {
+        match self {
+            SamplerKind::Uniform => Box::new(UniformSampler::new()),
+            SamplerKind::Stratified => Box::new(StratifiedSampler::new(samples_per_pixel)),
+            SamplerKind::Halton => Box::new(HaltonSampler::new()),
+            SamplerKind::Sobol => Box::new(SobolSampler::new()),
+        }
+    }
+}
+
+impl Display for SamplerKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SamplerKind::Uniform => "Uniform random",
+            SamplerKind::Stratified => "Stratified (jittered)",
+            SamplerKind::Halton => "Halton",
+            SamplerKind::Sobol => "Sobol (Owen scrambled)",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+///
+/// Maps a square sample to the unit disk, keeping the stratification of the input
+/// (Shirley & Chiu concentric mapping)
+///
+pub fn concentric_disk(u: [f64; 2]) -> [f64; 2] {
+    let x = 2.0 * u[0] - 1.0;
+    let y = 2.0 * u[1] - 1.0;
+    if x == 0.0 && y == 0.0 {
+        return [0.0, 0.0];
+    }
+
+    let (r, theta) = if x.abs() > y.abs() {
+        (x, std::f64::consts::FRAC_PI_4 * (y / x))
+    } else {
+        (
+            y,
+            std::f64::consts::FRAC_PI_2 - std::f64::consts::FRAC_PI_4 * (x / y),
+        )
+    };
+    [r * theta.cos(), r * theta.sin()]
+}
+
+///
+/// Maps a square sample to a point on the unit sphere with uniform density
+///
+pub fn uniform_sphere(u: [f64; 2]) -> Vec3 {
+    let z = 1.0 - 2.0 * u[0];
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * std::f64::consts::PI * u[1];
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+pub(crate) fn hash(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846ca68b);
+    x ^= x >> 16;
+    x
+}
+
+pub(crate) fn hash_combine(seed: u32, value: u32) -> u32 {
+    hash(
+        seed ^ value
+            .wrapping_add(0x9e3779b9)
+            .wrapping_add(seed << 6)
+            .wrapping_add(seed >> 2),
+    )
+}
+
+pub(crate) fn pixel_seed(pixel: [usize; 2]) -> u32 {
+    hash_combine(hash(pixel[0] as u32), pixel[1] as u32)
+}
+
+/// Converts 32 random bits to a float in [0, 1)
+pub(crate) fn to_unit_float(bits: u32) -> f64 {
+    bits as f64 * (1.0 / 4294967296.0)
+}
@@ -0,0 +1,87 @@
+use crate::renderer::sampler::{hash_combine, pixel_seed, to_unit_float, Sampler};
+
+///
+/// Owen-scrambled Sobol sequence. Only the first two Sobol dimensions are used, higher
+/// dimensions are built by padding independently shuffled and scrambled 2D sets
+/// (Burley, "Practical Hash-based Owen Scrambling").
+///
+pub struct SobolSampler {
+    pixel_seed: u32,
+    index: u32,
+    dimension: u32,
+}
+
+impl SobolSampler {
+    pub fn new() -> Self {
+        Self {
+            pixel_seed: 0,
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn next_seed(&mut self) -> u32 {
+        let seed = hash_combine(self.pixel_seed, self.dimension);
+        self.dimension += 1;
+        seed
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_sample(&mut self, pixel: [usize; 2], index: u32) {
+        self.pixel_seed = pixel_seed(pixel);
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        let seed = self.next_seed();
+        let index = nested_uniform_scramble(self.index, seed);
+        to_unit_float(nested_uniform_scramble(
+            index.reverse_bits(),
+            hash_combine(seed, 1),
+        ))
+    }
+
+    fn next_2d(&mut self) -> [f64; 2] {
+        let seed = self.next_seed();
+        let index = nested_uniform_scramble(self.index, seed);
+        [
+            to_unit_float(nested_uniform_scramble(
+                index.reverse_bits(),
+                hash_combine(seed, 1),
+            )),
+            to_unit_float(nested_uniform_scramble(
+                sobol_second_dimension(index),
+                hash_combine(seed, 2),
+            )),
+        ]
+    }
+}
+
+/// Second Sobol dimension, generated by the primitive polynomial x + 1
+fn sobol_second_dimension(mut index: u32) -> u32 {
+    let mut result = 0;
+    let mut direction = 1u32 << 31;
+    while index != 0 {
+        if index & 1 != 0 {
+            result ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+    result
+}
+
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50b47c);
+    x ^= x.wrapping_mul(0xb82f1e52);
+    x ^= x.wrapping_mul(0xc7afe638);
+    x ^= x.wrapping_mul(0x8d22f6e6);
+    x
+}
+
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
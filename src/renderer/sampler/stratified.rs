@@ -0,0 +1,98 @@
+use crate::renderer::sampler::{hash_combine, pixel_seed, to_unit_float, Sampler};
+
+///
+/// Jittered sampler: every dimension is split into as many strata as there are samples
+/// per pixel and each sample lands in a different stratum. The order of strata is shuffled
+/// independently for each pixel and dimension to avoid correlation between dimensions.
+///
+pub struct StratifiedSampler {
+    strata_1d: u32,
+    strata_per_axis: u32,
+    pixel_seed: u32,
+    index: u32,
+    dimension: u32,
+}
+
+impl StratifiedSampler {
+    pub fn new(samples_per_pixel: u32) -> Self {
+        let samples = samples_per_pixel.max(1);
+        Self {
+            strata_1d: samples,
+            strata_per_axis: (samples as f64).sqrt().ceil() as u32,
+            pixel_seed: 0,
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn next_seed(&mut self) -> u32 {
+        let seed = hash_combine(self.pixel_seed, self.dimension);
+        self.dimension += 1;
+        seed
+    }
+
+    fn jitter(&self, seed: u32, axis: u32) -> f64 {
+        to_unit_float(hash_combine(hash_combine(seed, self.index), axis))
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_sample(&mut self, pixel: [usize; 2], index: u32) {
+        self.pixel_seed = pixel_seed(pixel);
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        let seed = self.next_seed();
+        let stratum = permute(self.index % self.strata_1d, self.strata_1d, seed);
+        (stratum as f64 + self.jitter(seed, 0)) / self.strata_1d as f64
+    }
+
+    fn next_2d(&mut self) -> [f64; 2] {
+        let seed = self.next_seed();
+        let n = self.strata_per_axis;
+        let stratum = permute(self.index % (n * n), n * n, seed);
+        [
+            ((stratum % n) as f64 + self.jitter(seed, 0)) / n as f64,
+            ((stratum / n) as f64 + self.jitter(seed, 1)) / n as f64,
+        ]
+    }
+}
+
+///
+/// Random permutation of `i` in range [0, l) selected by `p`
+/// (Kensler, "Correlated Multi-Jittered Sampling")
+///
+fn permute(mut i: u32, l: u32, p: u32) -> u32 {
+    let mut w = l - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < l {
+            break;
+        }
+    }
+    i.wrapping_add(p) % l
+}
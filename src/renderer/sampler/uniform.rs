@@ -0,0 +1,25 @@
+use crate::renderer::sampler::Sampler;
+use rand::rngs::ThreadRng;
+use rand::{thread_rng, Rng};
+
+pub struct UniformSampler {
+    rng: ThreadRng,
+}
+
+impl UniformSampler {
+    pub fn new() -> Self {
+        Self { rng: thread_rng() }
+    }
+}
+
+impl Sampler for UniformSampler {
+    fn start_sample(&mut self, _pixel: [usize; 2], _index: u32) {}
+
+    fn next_1d(&mut self) -> f64 {
+        self.rng.gen::<f64>()
+    }
+
+    fn next_2d(&mut self) -> [f64; 2] {
+        [self.rng.gen::<f64>(), self.rng.gen::<f64>()]
+    }
+}
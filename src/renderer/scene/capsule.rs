@@ -0,0 +1,104 @@
+use crate::math::{solve_quadratic, Point3};
+use crate::renderer::bvh::Aabb;
+use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::sampler::Sampler;
+use crate::{Color3, Ray, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+///
+/// Points within `radius` of the vertical segment of length `height` centered at `center`:
+/// a cylinder with hemispherical ends
+///
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Capsule {
+    pub center: Point3,
+    pub radius: f64,
+    pub height: f64,
+    pub color: Color3,
+}
+
+impl Capsule {
+    pub fn new(center: Point3, radius: f64, height: f64) -> Self {
+        Self {
+            center,
+            radius,
+            height,
+            color: Color3::splat(1.0),
+        }
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        let extent = Vec3::new(self.radius, self.height / 2.0 + self.radius, self.radius);
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+
+    /// Parameters of the entry and exit points, the capsule is convex
+    fn roots(&self, ray: &Ray) -> Vec<f64> {
+        let origin = *ray.origin() - self.center;
+        let direction = ray.direction();
+        let half_height = self.height / 2.0;
+        let radius2 = self.radius * self.radius;
+
+        let side = solve_quadratic(
+            direction.x() * direction.x() + direction.z() * direction.z(),
+            2.0 * (origin.x() * direction.x() + origin.z() * direction.z()),
+            origin.x() * origin.x() + origin.z() * origin.z() - radius2,
+        )
+        .into_iter()
+        .filter(|t| (origin.y() + t * direction.y()).abs() <= half_height);
+        // each end sphere only counts beyond its end of the segment
+        let ends = [-half_height, half_height].into_iter().flat_map(|end| {
+            let oc = origin - Vec3::new(0.0, end, 0.0);
+            solve_quadratic(
+                direction.length_squared(),
+                2.0 * Vec3::dot(&oc, direction),
+                oc.length_squared() - radius2,
+            )
+            .into_iter()
+            .filter(move |t| (oc.y() + t * direction.y()) * end >= 0.0)
+        });
+
+        let roots: Vec<f64> = side.chain(ends).collect();
+        match roots.is_empty() {
+            true => vec![],
+            false => {
+                let entry = roots.iter().copied().fold(f64::INFINITY, f64::min);
+                let exit = roots.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                vec![entry, exit]
+            }
+        }
+    }
+
+    fn hit_at(&self, ray: &Ray, t: f64) -> Hit {
+        let local = ray.at(t) - self.center;
+        let half_height = self.height / 2.0;
+        let axis_point = Vec3::new(0.0, local.y().clamp(-half_height, half_height), 0.0);
+        let outward_normal = (local - axis_point) / self.radius;
+        let uv = [
+            (local.z().atan2(local.x()) + PI) / (2.0 * PI),
+            (local.y() + half_height + self.radius) / (self.height + 2.0 * self.radius),
+        ];
+        Hit::diffuse(ray, t, outward_normal, uv, self.color)
+    }
+}
+
+impl Hittable for Capsule {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _sampler: &mut dyn Sampler) -> Option<Hit> {
+        self.roots(ray)
+            .into_iter()
+            .find(|t| (t_min..=t_max).contains(t))
+            .map(|t| self.hit_at(ray, t))
+    }
+
+    fn intersections(&self, ray: &Ray) -> Vec<Hit> {
+        self.roots(ray)
+            .into_iter()
+            .map(|t| self.hit_at(ray, t))
+            .collect()
+    }
+
+    fn name(&self) -> String {
+        format!("Capsule at {}", self.center)
+    }
+}
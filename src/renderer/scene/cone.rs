@@ -0,0 +1,111 @@
+use crate::math::{solve_quadratic, Point3};
+use crate::renderer::bvh::Aabb;
+use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::sampler::Sampler;
+use crate::{Color3, Ray, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+///
+/// Cone closed by its base, standing on `center` with the apex `height` above it
+///
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cone {
+    pub center: Point3,
+    pub radius: f64,
+    pub height: f64,
+    pub color: Color3,
+}
+
+impl Cone {
+    pub fn new(center: Point3, radius: f64, height: f64) -> Self {
+        Self {
+            center,
+            radius,
+            height,
+            color: Color3::splat(1.0),
+        }
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        Aabb::new(
+            self.center - Vec3::new(self.radius, 0.0, self.radius),
+            self.center + Vec3::new(self.radius, self.height, self.radius),
+        )
+    }
+
+    /// Parameters of all points where the ray crosses the side or the base, nearest first
+    fn roots(&self, ray: &Ray) -> Vec<f64> {
+        let origin = *ray.origin() - self.center;
+        let direction = ray.direction();
+        // x² + z² = k² (height - y)²
+        let k = self.radius / self.height;
+        let k2 = k * k;
+        let apex_distance = self.height - origin.y();
+
+        let mut roots: Vec<f64> = solve_quadratic(
+            direction.x() * direction.x() + direction.z() * direction.z() -
+                k2 * direction.y() * direction.y(),
+            2.0 * (origin.x() * direction.x() +
+                origin.z() * direction.z() +
+                k2 * apex_distance * direction.y()),
+            origin.x() * origin.x() + origin.z() * origin.z() - k2 * apex_distance * apex_distance,
+        )
+        .into_iter()
+        .filter(|t| (0.0..=self.height).contains(&(origin.y() + t * direction.y())))
+        .collect();
+
+        if direction.y() != 0.0 {
+            let t = -origin.y() / direction.y();
+            let x = origin.x() + t * direction.x();
+            let z = origin.z() + t * direction.z();
+            if x * x + z * z <= self.radius * self.radius {
+                roots.push(t);
+            }
+        }
+        roots.sort_by(f64::total_cmp);
+        roots
+    }
+
+    fn hit_at(&self, ray: &Ray, t: f64) -> Hit {
+        let local = ray.at(t) - self.center;
+        let side_distance = (local.x() * local.x() + local.z() * local.z()).sqrt();
+        let side_radius = self.radius * (1.0 - local.y() / self.height);
+
+        let (outward_normal, uv) = if local.y().abs() < (side_distance - side_radius).abs() {
+            let uv = [
+                0.5 + local.x() / (2.0 * self.radius),
+                0.5 + local.z() / (2.0 * self.radius),
+            ];
+            (Vec3::new(0.0, -1.0, 0.0), uv)
+        } else {
+            // gradient of x² + z² - k² (height - y)²
+            let k = self.radius / self.height;
+            let normal =
+                Vec3::new(local.x(), k * k * (self.height - local.y()), local.z()).normalized();
+            let around = (local.z().atan2(local.x()) + PI) / (2.0 * PI);
+            (normal, [around, local.y() / self.height])
+        };
+        Hit::diffuse(ray, t, outward_normal, uv, self.color)
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _sampler: &mut dyn Sampler) -> Option<Hit> {
+        self.roots(ray)
+            .into_iter()
+            .find(|t| (t_min..=t_max).contains(t))
+            .map(|t| self.hit_at(ray, t))
+    }
+
+    fn intersections(&self, ray: &Ray) -> Vec<Hit> {
+        self.roots(ray)
+            .into_iter()
+            .map(|t| self.hit_at(ray, t))
+            .collect()
+    }
+
+    fn name(&self) -> String {
+        format!("Cone at {}", self.center)
+    }
+}
@@ -0,0 +1,79 @@
+use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::material::Material;
+use crate::renderer::sampler::Sampler;
+use crate::renderer::scene::SceneObject;
+use crate::{Color3, Ray, Vec3};
+use serde::{Deserialize, Serialize};
+
+///
+/// Volume of constant density (fog, smoke) filling the inside of a boundary object.
+/// Rays scatter inside after an exponentially distributed distance.
+///
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConstantMedium {
+    pub boundary: Box<SceneObject>,
+    pub density: f64,
+    pub color: Color3,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: SceneObject, density: f64) -> Self {
+        Self {
+            boundary: Box::new(boundary),
+            density,
+            color: Color3::splat(1.0),
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<Hit> {
+        // entry and exit of the boundary along the whole line, the ray can start inside
+        let entry = self
+            .boundary
+            .hit(ray, f64::NEG_INFINITY, f64::INFINITY, sampler)?;
+        let exit = self
+            .boundary
+            .hit(ray, entry.t + 0.0001, f64::INFINITY, sampler)?;
+
+        let t_enter = entry.t.max(t_min);
+        let t_exit = exit.t.min(t_max);
+        if t_enter >= t_exit {
+            return None;
+        }
+        let t_enter = t_enter.max(0.0);
+
+        let ray_length = ray.direction().length();
+        let distance_inside = (t_exit - t_enter) * ray_length;
+        let hit_distance = -(1.0 - sampler.next_1d()).ln() / self.density;
+        if hit_distance > distance_inside {
+            return None;
+        }
+
+        let t = t_enter + hit_distance / ray_length;
+        Some(Hit {
+            point: ray.at(t),
+            // arbitrary, there is no surface inside the volume
+            normal: Vec3::new(1.0, 0.0, 0.0),
+            t,
+            front_face: true,
+            uv: [0.0, 0.0],
+            surface: self.color,
+            material: Material::Isotropic,
+            emission: Color3::splat(0.0),
+            object: 0,
+        })
+    }
+
+    fn intersections(&self, _ray: &Ray) -> Vec<Hit> {
+        vec![]
+    }
+
+    fn name(&self) -> String {
+        format!(
+            "Constant medium of density {} in {}",
+            self.density,
+            self.boundary.name()
+        )
+    }
+}
@@ -0,0 +1,122 @@
+use crate::renderer::bvh::Aabb;
+use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::sampler::Sampler;
+use crate::renderer::scene::SceneObject;
+use crate::Ray;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+///
+/// Boolean operation combining the solids of a [`Csg`] object
+///
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    /// Second object carved out of the first one
+    Difference,
+}
+
+impl CsgOperation {
+    pub const ALL: [CsgOperation; 3] = [
+        CsgOperation::Union,
+        CsgOperation::Intersection,
+        CsgOperation::Difference,
+    ];
+
+    fn inside(&self, first: bool, second: bool) -> bool {
+        match self {
+            CsgOperation::Union => first || second,
+            CsgOperation::Intersection => first && second,
+            CsgOperation::Difference => first && !second,
+        }
+    }
+}
+
+impl Display for CsgOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            CsgOperation::Union => "Union",
+            CsgOperation::Intersection => "Intersection",
+            CsgOperation::Difference => "Difference",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+///
+/// Solid built from two closed objects by a boolean operation on the intervals the ray
+/// spends inside each of them
+///
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Csg {
+    pub operation: CsgOperation,
+    pub first: Box<SceneObject>,
+    pub second: Box<SceneObject>,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, first: SceneObject, second: SceneObject) -> Self {
+        Self {
+            operation,
+            first: Box::new(first),
+            second: Box::new(second),
+        }
+    }
+
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        let first = self.first.bounding_box();
+        match self.operation {
+            CsgOperation::Union => Some(first?.union(&self.second.bounding_box()?)),
+            // the result lies within the first object
+            CsgOperation::Intersection | CsgOperation::Difference => first,
+        }
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _sampler: &mut dyn Sampler) -> Option<Hit> {
+        self.intersections(ray)
+            .into_iter()
+            .find(|hit| (t_min..=t_max).contains(&hit.t))
+    }
+
+    fn intersections(&self, ray: &Ray) -> Vec<Hit> {
+        let first = self.first.intersections(ray);
+        let second = self.second.intersections(ray);
+        // intersections cover the whole line, so it starts outside of both objects
+        let mut inside = [false, false];
+
+        let mut events: Vec<(usize, Hit)> = first
+            .into_iter()
+            .map(|hit| (0, hit))
+            .chain(second.into_iter().map(|hit| (1, hit)))
+            .collect();
+        events.sort_by(|a, b| a.1.t.total_cmp(&b.1.t));
+
+        // walk the entry and exit points of both interval lists, the ones where the
+        // combined inside state changes bound the intervals of the result
+        let mut result = vec![];
+        for (object, mut hit) in events {
+            let was_inside = self.operation.inside(inside[0], inside[1]);
+            inside[object] = hit.front_face;
+            let is_inside = self.operation.inside(inside[0], inside[1]);
+            if was_inside != is_inside {
+                // normals already face the ray, only entering and leaving can swap
+                // (surfaces of the carved out object)
+                hit.front_face = is_inside;
+                result.push(hit);
+            }
+        }
+        result
+    }
+
+    fn name(&self) -> String {
+        format!(
+            "{} of {} and {}",
+            self.operation,
+            self.first.name(),
+            self.second.name()
+        )
+    }
+}
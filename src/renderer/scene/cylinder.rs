@@ -0,0 +1,104 @@
+use crate::math::{solve_quadratic, Point3};
+use crate::renderer::bvh::Aabb;
+use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::sampler::Sampler;
+use crate::{Color3, Ray, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+///
+/// Cylinder closed by caps, its axis is vertical and goes through the center
+///
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cylinder {
+    pub center: Point3,
+    pub radius: f64,
+    pub height: f64,
+    pub color: Color3,
+}
+
+impl Cylinder {
+    pub fn new(center: Point3, radius: f64, height: f64) -> Self {
+        Self {
+            center,
+            radius,
+            height,
+            color: Color3::splat(1.0),
+        }
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        let extent = Vec3::new(self.radius, self.height / 2.0, self.radius);
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+
+    /// Parameters of all points where the ray crosses the side or the caps, nearest first
+    fn roots(&self, ray: &Ray) -> Vec<f64> {
+        let origin = *ray.origin() - self.center;
+        let direction = ray.direction();
+        let half_height = self.height / 2.0;
+
+        let mut roots: Vec<f64> = solve_quadratic(
+            direction.x() * direction.x() + direction.z() * direction.z(),
+            2.0 * (origin.x() * direction.x() + origin.z() * direction.z()),
+            origin.x() * origin.x() + origin.z() * origin.z() - self.radius * self.radius,
+        )
+        .into_iter()
+        .filter(|t| (origin.y() + t * direction.y()).abs() <= half_height)
+        .collect();
+
+        if direction.y() != 0.0 {
+            for cap in [-half_height, half_height] {
+                let t = (cap - origin.y()) / direction.y();
+                let x = origin.x() + t * direction.x();
+                let z = origin.z() + t * direction.z();
+                if x * x + z * z <= self.radius * self.radius {
+                    roots.push(t);
+                }
+            }
+        }
+        roots.sort_by(f64::total_cmp);
+        roots
+    }
+
+    fn hit_at(&self, ray: &Ray, t: f64) -> Hit {
+        let local = ray.at(t) - self.center;
+        let half_height = self.height / 2.0;
+        let side_distance = (local.x() * local.x() + local.z() * local.z()).sqrt();
+        let around = (local.z().atan2(local.x()) + PI) / (2.0 * PI);
+
+        // whichever surface the point is closest to, caps map their disk onto the square
+        let (outward_normal, uv) =
+            if (local.y().abs() - half_height).abs() < (side_distance - self.radius).abs() {
+                let uv = [
+                    0.5 + local.x() / (2.0 * self.radius),
+                    0.5 + local.z() / (2.0 * self.radius),
+                ];
+                (Vec3::new(0.0, local.y().signum(), 0.0), uv)
+            } else {
+                let normal = Vec3::new(local.x(), 0.0, local.z()) / self.radius;
+                (normal, [around, (local.y() + half_height) / self.height])
+            };
+        Hit::diffuse(ray, t, outward_normal, uv, self.color)
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _sampler: &mut dyn Sampler) -> Option<Hit> {
+        self.roots(ray)
+            .into_iter()
+            .find(|t| (t_min..=t_max).contains(t))
+            .map(|t| self.hit_at(ray, t))
+    }
+
+    fn intersections(&self, ray: &Ray) -> Vec<Hit> {
+        self.roots(ray)
+            .into_iter()
+            .map(|t| self.hit_at(ray, t))
+            .collect()
+    }
+
+    fn name(&self) -> String {
+        format!("Cylinder at {}", self.center)
+    }
+}
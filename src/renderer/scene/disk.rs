@@ -0,0 +1,68 @@
+use crate::math::Point3;
+use crate::renderer::bvh::Aabb;
+use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::sampler::Sampler;
+use crate::{Color3, Ray, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+///
+/// Flat round disk lying in the xz plane, facing up
+///
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Disk {
+    pub center: Point3,
+    pub radius: f64,
+    pub color: Color3,
+}
+
+impl Disk {
+    pub fn new(center: Point3, radius: f64) -> Self {
+        Self {
+            center,
+            radius,
+            color: Color3::splat(1.0),
+        }
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        // slightly thickened so rays along the disk's plane don't degenerate the box
+        let extent = Vec3::new(self.radius, 1e-6, self.radius);
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _sampler: &mut dyn Sampler) -> Option<Hit> {
+        let t = (self.center.y() - ray.origin().y()) / ray.direction().y();
+        if !(t_min..=t_max).contains(&t) {
+            return None;
+        }
+        let offset = ray.at(t) - self.center;
+        let distance = offset.length();
+        if distance > self.radius {
+            return None;
+        }
+        // polar coordinates: angle around the axis and distance from the center
+        let uv = [
+            (offset.z().atan2(offset.x()) + PI) / (2.0 * PI),
+            distance / self.radius,
+        ];
+        Some(Hit::diffuse(
+            ray,
+            t,
+            Vec3::new(0.0, 1.0, 0.0),
+            uv,
+            self.color,
+        ))
+    }
+
+    fn intersections(&self, _ray: &Ray) -> Vec<Hit> {
+        // the disk doesn't enclose any volume
+        vec![]
+    }
+
+    fn name(&self) -> String {
+        format!("Disk at {}", self.center)
+    }
+}
@@ -0,0 +1,359 @@
+use crate::renderer::bvh::{Aabb, Bvh};
+use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::material::Material;
+use crate::renderer::sampler::Sampler;
+use crate::renderer::scene::transformed::Transform;
+use crate::renderer::scene::SceneObject;
+use crate::{Color3, Ray, Vec3};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+thread_local! {
+    /// Shared geometries of the scene this thread saves or loads, see [`with_geometry_table`]
+    static GEOMETRY_TABLE: RefCell<Option<Vec<Arc<Geometry>>>> = const { RefCell::new(None) };
+}
+
+///
+/// Runs `f` with the table of shared geometries, instanced objects (de)serialized by it
+/// refer to their geometry by its index in the table instead of storing the objects.
+/// Loading starts with an empty table that [`SharedGeometry`] fills.
+///
+pub(crate) fn with_geometry_table<T>(geometries: Vec<Arc<Geometry>>, f: impl FnOnce() -> T) -> T {
+    GEOMETRY_TABLE.with(|table| *table.borrow_mut() = Some(geometries));
+    let result = f();
+    GEOMETRY_TABLE.with(|table| table.borrow_mut().take());
+    result
+}
+
+///
+/// Entry of the geometry table in a saved scene, loading it adds the geometry to the
+/// table, so entries can use the ones before them
+///
+pub(crate) struct SharedGeometry(pub(crate) Arc<Geometry>);
+
+impl Serialize for SharedGeometry {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        self.0.objects.serialize(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for SharedGeometry {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let geometry = Arc::new(Geometry::new(Vec::deserialize(d)?));
+        GEOMETRY_TABLE.with(|table| {
+            if let Some(geometries) = table.borrow_mut().as_mut() {
+                geometries.push(geometry.clone());
+            }
+        });
+        Ok(SharedGeometry(geometry))
+    }
+}
+
+///
+/// Objects shared by all instances of an [`Instanced`] object with the hierarchy over
+/// them, the bottom level of the two-level BVH
+///
+#[derive(Clone)]
+pub struct Geometry {
+    objects: Vec<SceneObject>,
+    bvh: Bvh,
+}
+
+impl Geometry {
+    pub fn new(objects: Vec<SceneObject>) -> Self {
+        let bounds: Vec<Option<Aabb>> = objects.iter().map(|o| o.bounding_box()).collect();
+        Self {
+            bvh: Bvh::build(&bounds),
+            objects,
+        }
+    }
+
+    pub fn objects(&self) -> &[SceneObject] {
+        &self.objects
+    }
+
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<Hit> {
+        self.bvh.closest(ray, t_min, t_max, |index, t_max| {
+            let hit = self.objects[index].hit(ray, t_min, t_max, sampler)?;
+            Some((hit.t, hit))
+        })
+    }
+
+    fn intersections(&self, ray: &Ray) -> Vec<Hit> {
+        let mut hits: Vec<Hit> = self
+            .bvh
+            .candidates(ray)
+            .into_iter()
+            .flat_map(|index| self.objects[index].intersections(ray))
+            .collect();
+        hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+        hits
+    }
+
+    fn memory_size(&self, geometries: &mut HashSet<*const Geometry>) -> usize {
+        self.objects.capacity() * std::mem::size_of::<SceneObject>() +
+            self.objects
+                .iter()
+                .map(|o| o.data_size(geometries))
+                .sum::<usize>() +
+            self.bvh.memory_size()
+    }
+}
+
+///
+/// Placement of one copy of the shared geometry, the color and material replace the ones
+/// of the geometry when set
+///
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Instance {
+    pub transform: Transform,
+    pub color: Option<Color3>,
+    pub material: Option<Material>,
+}
+
+impl Instance {
+    fn world_hit(&self, ray: &Ray, mut hit: Hit) -> Hit {
+        hit.point = ray.at(hit.t);
+        hit.normal = self.transform.normal_to_world(hit.normal);
+        if let Some(color) = self.color {
+            hit.surface = color;
+        }
+        if let Some(material) = self.material {
+            hit.material = material;
+        }
+        hit
+    }
+}
+
+///
+/// Many copies of the same geometry, which is stored once and shared by clones of the
+/// object. Rays find the instances they pass through in a hierarchy over the instances
+/// (the top level) and test the geometry in its own one.
+///
+#[derive(Clone, Deserialize)]
+#[serde(try_from = "InstancedData")]
+pub struct Instanced {
+    geometry: Arc<Geometry>,
+    instances: Vec<Instance>,
+    bvh: Bvh,
+}
+
+///
+/// Serialized form, hierarchies are rebuilt when the object is loaded. Inside the geometry
+/// table the geometry is the index of its entry, otherwise the objects are stored inline.
+///
+#[derive(Serialize, Deserialize)]
+struct InstancedData<'a> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    geometry: Option<usize>,
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    objects: Cow<'a, [SceneObject]>,
+    instances: Cow<'a, [Instance]>,
+}
+
+impl TryFrom<InstancedData<'static>> for Instanced {
+    type Error = String;
+
+    fn try_from(data: InstancedData) -> Result<Self, Self::Error> {
+        let instances = data.instances.into_owned();
+        match data.geometry {
+            Some(index) => {
+                let geometry = GEOMETRY_TABLE
+                    .with(|table| table.borrow().as_ref()?.get(index).cloned())
+                    .ok_or_else(|| format!("no shared geometry {}", index))?;
+                Ok(Instanced::with_geometry(geometry, instances))
+            }
+            None => Ok(Instanced::new(data.objects.into_owned(), instances)),
+        }
+    }
+}
+
+impl Serialize for Instanced {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let index = GEOMETRY_TABLE.with(|table| {
+            table
+                .borrow()
+                .as_ref()?
+                .iter()
+                .position(|geometry| Arc::ptr_eq(geometry, &self.geometry))
+        });
+        InstancedData {
+            geometry: index,
+            objects: match index {
+                Some(_) => Cow::Borrowed(&[]),
+                None => Cow::Borrowed(&self.geometry.objects),
+            },
+            instances: Cow::Borrowed(&self.instances),
+        }
+        .serialize(s)
+    }
+}
+
+impl Instanced {
+    pub fn new(objects: Vec<SceneObject>, instances: Vec<Instance>) -> Self {
+        Self::with_geometry(Arc::new(Geometry::new(objects)), instances)
+    }
+
+    fn with_geometry(geometry: Arc<Geometry>, instances: Vec<Instance>) -> Self {
+        let mut instanced = Self {
+            geometry,
+            instances,
+            bvh: Bvh::default(),
+        };
+        instanced.rebuild();
+        instanced
+    }
+
+    /// Rebuild the top level hierarchy after instances or the geometry changed
+    fn rebuild(&mut self) {
+        let geometry_bounds = self.geometry.bvh.bounds();
+        let bounds: Vec<Option<Aabb>> = self
+            .instances
+            .iter()
+            .map(|instance| geometry_bounds.map(|bounds| instance.transform.bounds(&bounds)))
+            .collect();
+        self.bvh = Bvh::build(&bounds);
+    }
+
+    pub fn geometry(&self) -> &Geometry {
+        &self.geometry
+    }
+
+    ///
+    /// Replace object of the shared geometry, the geometry gets copied first if other
+    /// objects share it
+    ///
+    pub fn set_geometry_object(&mut self, index: usize, object: SceneObject) {
+        let mut objects = self.geometry.objects.clone();
+        objects[index] = object;
+        self.geometry = Arc::new(Geometry::new(objects));
+        self.rebuild();
+    }
+
+    ///
+    /// Add the geometry to `geometries` if it isn't there yet, after the geometries its
+    /// objects use
+    ///
+    pub(crate) fn collect_geometries(&self, geometries: &mut Vec<Arc<Geometry>>) {
+        if !geometries
+            .iter()
+            .any(|geometry| Arc::ptr_eq(geometry, &self.geometry))
+        {
+            for object in &self.geometry.objects {
+                object.collect_geometries(geometries);
+            }
+            geometries.push(self.geometry.clone());
+        }
+    }
+
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    pub fn set_instance(&mut self, index: usize, instance: Instance) {
+        self.instances[index] = instance;
+        self.rebuild();
+    }
+
+    /// Average position of the instances
+    pub fn position(&self) -> Vec3 {
+        let sum = self
+            .instances
+            .iter()
+            .fold(Vec3::splat(0.0), |sum, instance| {
+                sum + instance.transform.translation
+            });
+        sum / self.instances.len().max(1) as f64
+    }
+
+    pub fn translate(&mut self, offset: Vec3) {
+        for instance in &mut self.instances {
+            instance.transform.translation = instance.transform.translation + offset;
+        }
+        self.rebuild();
+    }
+
+    pub fn color(&self) -> Color3 {
+        match self.instances.first().and_then(|instance| instance.color) {
+            Some(color) => color,
+            None => self
+                .geometry
+                .objects
+                .first()
+                .map_or(Color3::splat(1.0), |object| object.color()),
+        }
+    }
+
+    pub fn set_color(&mut self, color: Color3) {
+        for instance in &mut self.instances {
+            instance.color = Some(color);
+        }
+    }
+
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounds()
+    }
+
+    ///
+    /// Bytes used by the object, the shared geometry is counted only if it isn't in
+    /// `geometries` already, which it gets added to
+    ///
+    pub fn data_size(&self, geometries: &mut HashSet<*const Geometry>) -> usize {
+        let geometry_size = match geometries.insert(Arc::as_ptr(&self.geometry)) {
+            true => self.geometry.memory_size(geometries),
+            false => 0,
+        };
+        self.instances.capacity() * std::mem::size_of::<Instance>() +
+            self.bvh.memory_size() +
+            geometry_size
+    }
+}
+
+impl PartialEq for Instanced {
+    fn eq(&self, other: &Self) -> bool {
+        // edits replace the geometry, so a shared one is unchanged without comparing its data
+        Arc::ptr_eq(&self.geometry, &other.geometry) && self.instances == other.instances
+    }
+}
+
+impl Hittable for Instanced {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<Hit> {
+        self.bvh.closest(ray, t_min, t_max, |index, t_max| {
+            let instance = &self.instances[index];
+            let local_ray = instance.transform.local_ray(ray)?;
+            let hit = self.geometry.hit(&local_ray, t_min, t_max, sampler)?;
+            Some((hit.t, instance.world_hit(ray, hit)))
+        })
+    }
+
+    fn intersections(&self, ray: &Ray) -> Vec<Hit> {
+        let mut hits = vec![];
+        for index in self.bvh.candidates(ray) {
+            let instance = &self.instances[index];
+            if let Some(local_ray) = instance.transform.local_ray(ray) {
+                hits.extend(
+                    self.geometry
+                        .intersections(&local_ray)
+                        .into_iter()
+                        .map(|hit| instance.world_hit(ray, hit)),
+                );
+            }
+        }
+        hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+        hits
+    }
+
+    fn name(&self) -> String {
+        match self.geometry.objects.as_slice() {
+            [object] => format!("{} instances of {}", self.instances.len(), object.name()),
+            objects => format!(
+                "{} instances of {} objects",
+                self.instances.len(),
+                objects.len()
+            ),
+        }
+    }
+}
@@ -1,43 +1,710 @@
 use crate::math::Point3;
+use crate::renderer::animation::{Animation, ObjectTracks};
+use crate::renderer::bvh::{Aabb, Bvh};
+use crate::renderer::camera::CameraView;
 use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::sampler::Sampler;
+use crate::renderer::scene::capsule::Capsule;
+use crate::renderer::scene::cone::Cone;
+use crate::renderer::scene::constant_medium::ConstantMedium;
+use crate::renderer::scene::csg::{Csg, CsgOperation};
+use crate::renderer::scene::cylinder::Cylinder;
+use crate::renderer::scene::disk::Disk;
+use crate::renderer::scene::instanced::{
+    with_geometry_table, Geometry, Instance, Instanced, SharedGeometry,
+};
+use crate::renderer::scene::sdf::{SdfObject, SdfShape};
 use crate::renderer::scene::sphere::Sphere;
-use crate::Ray;
+use crate::renderer::scene::torus::Torus;
+use crate::renderer::scene::transformed::{Transform, Transformed};
+use crate::renderer::scene::voxel_volume::{VoxelGrid, VoxelSource, VoxelVolume};
+use crate::{Color3, Ray, Vec3};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, OnceLock};
 
+pub(crate) mod capsule;
+pub(crate) mod cone;
+pub(crate) mod constant_medium;
+pub(crate) mod csg;
+pub(crate) mod cylinder;
+pub(crate) mod disk;
+pub(crate) mod instanced;
+pub(crate) mod sdf;
 pub(crate) mod sphere;
+pub(crate) mod torus;
+pub(crate) mod transformed;
+pub(crate) mod voxel_volume;
 
-#[derive(Clone)]
+thread_local! {
+    /// Ray-object intersection tests done by this thread, see [`take_intersection_tests`]
+    static INTERSECTION_TESTS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Number of intersection tests since the last call on this thread
+pub fn take_intersection_tests() -> u64 {
+    INTERSECTION_TESTS.with(|tests| tests.replace(0))
+}
+
+pub(crate) fn count_intersection_tests(tests: u64) {
+    INTERSECTION_TESTS.with(|counter| counter.set(counter.get() + tests));
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum SceneObject {
     Sphere(Sphere),
+    Cylinder(Cylinder),
+    Cone(Cone),
+    Disk(Disk),
+    Torus(Torus),
+    Capsule(Capsule),
+    ConstantMedium(ConstantMedium),
+    VoxelVolume(VoxelVolume),
+    Transformed(Transformed),
+    Csg(Csg),
+    Sdf(SdfObject),
+    Instanced(Instanced),
+}
+
+impl SceneObject {
+    pub fn object_type(&self) -> ObjectType {
+        match self {
+            SceneObject::Sphere(_) => ObjectType::Sphere,
+            SceneObject::Cylinder(_) => ObjectType::Cylinder,
+            SceneObject::Cone(_) => ObjectType::Cone,
+            SceneObject::Disk(_) => ObjectType::Disk,
+            SceneObject::Torus(_) => ObjectType::Torus,
+            SceneObject::Capsule(_) => ObjectType::Capsule,
+            SceneObject::ConstantMedium(_) => ObjectType::ConstantMedium,
+            SceneObject::VoxelVolume(_) => ObjectType::VoxelVolume,
+            SceneObject::Transformed(_) => ObjectType::Transformed,
+            SceneObject::Csg(_) => ObjectType::Csg,
+            SceneObject::Sdf(_) => ObjectType::Sdf,
+            SceneObject::Instanced(_) => ObjectType::Instanced,
+        }
+    }
+
+    /// Point the object is placed at, the one keyframed by position tracks
+    pub fn position(&self) -> Point3 {
+        match self {
+            SceneObject::Sphere(sphere) => sphere.center,
+            SceneObject::Cylinder(cylinder) => cylinder.center,
+            SceneObject::Cone(cone) => cone.center,
+            SceneObject::Disk(disk) => disk.center,
+            SceneObject::Torus(torus) => torus.center,
+            SceneObject::Capsule(capsule) => capsule.center,
+            SceneObject::ConstantMedium(medium) => medium.boundary.position(),
+            SceneObject::VoxelVolume(volume) => (volume.min + volume.max) / 2.0,
+            SceneObject::Transformed(transformed) => transformed.start.translation,
+            SceneObject::Csg(csg) => csg.first.position(),
+            SceneObject::Sdf(sdf) => sdf.center,
+            SceneObject::Instanced(instanced) => instanced.position(),
+        }
+    }
+
+    ///
+    /// Move the object to `position`, objects supporting motion blur move on to
+    /// `next_position` during the shutter interval
+    ///
+    pub fn set_position(&mut self, position: Point3, next_position: Point3) {
+        match self {
+            SceneObject::Sphere(sphere) => {
+                sphere.center = position;
+                sphere.end_center = (next_position != position).then_some(next_position);
+            }
+            SceneObject::Cylinder(cylinder) => cylinder.center = position,
+            SceneObject::Cone(cone) => cone.center = position,
+            SceneObject::Disk(disk) => disk.center = position,
+            SceneObject::Torus(torus) => torus.center = position,
+            SceneObject::Capsule(capsule) => capsule.center = position,
+            SceneObject::ConstantMedium(medium) => {
+                medium.boundary.set_position(position, next_position)
+            }
+            SceneObject::VoxelVolume(volume) => {
+                let offset = position - (volume.min + volume.max) / 2.0;
+                volume.min = volume.min + offset;
+                volume.max = volume.max + offset;
+            }
+            SceneObject::Transformed(transformed) => {
+                transformed.start.translation = position;
+                transformed.end.translation = next_position;
+            }
+            SceneObject::Csg(csg) => {
+                // the second object keeps its offset from the first one
+                let offset = csg.second.position() - csg.first.position();
+                csg.first.set_position(position, next_position);
+                csg.second
+                    .set_position(position + offset, next_position + offset);
+            }
+            SceneObject::Sdf(sdf) => sdf.center = position,
+            SceneObject::Instanced(instanced) => {
+                instanced.translate(position - instanced.position())
+            }
+        }
+    }
+
+    pub fn color(&self) -> Color3 {
+        match self {
+            SceneObject::Sphere(sphere) => sphere.color,
+            SceneObject::Cylinder(cylinder) => cylinder.color,
+            SceneObject::Cone(cone) => cone.color,
+            SceneObject::Disk(disk) => disk.color,
+            SceneObject::Torus(torus) => torus.color,
+            SceneObject::Capsule(capsule) => capsule.color,
+            SceneObject::ConstantMedium(medium) => medium.color,
+            SceneObject::VoxelVolume(volume) => volume.color,
+            SceneObject::Transformed(transformed) => transformed.object.color(),
+            SceneObject::Csg(csg) => csg.first.color(),
+            SceneObject::Sdf(sdf) => sdf.color,
+            SceneObject::Instanced(instanced) => instanced.color(),
+        }
+    }
+
+    pub fn set_color(&mut self, color: Color3) {
+        match self {
+            SceneObject::Sphere(sphere) => sphere.color = color,
+            SceneObject::Cylinder(cylinder) => cylinder.color = color,
+            SceneObject::Cone(cone) => cone.color = color,
+            SceneObject::Disk(disk) => disk.color = color,
+            SceneObject::Torus(torus) => torus.color = color,
+            SceneObject::Capsule(capsule) => capsule.color = color,
+            SceneObject::ConstantMedium(medium) => medium.color = color,
+            SceneObject::VoxelVolume(volume) => volume.color = color,
+            SceneObject::Transformed(transformed) => transformed.object.set_color(color),
+            SceneObject::Csg(csg) => {
+                csg.first.set_color(color);
+                csg.second.set_color(color);
+            }
+            SceneObject::Sdf(sdf) => sdf.color = color,
+            SceneObject::Instanced(instanced) => instanced.set_color(color),
+        }
+    }
+
+    ///
+    /// Box containing the object during the whole shutter interval, `None` for objects
+    /// without bounds
+    ///
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        match self {
+            SceneObject::Sphere(sphere) => Some(sphere.bounding_box()),
+            SceneObject::Cylinder(cylinder) => Some(cylinder.bounding_box()),
+            SceneObject::Cone(cone) => Some(cone.bounding_box()),
+            SceneObject::Disk(disk) => Some(disk.bounding_box()),
+            SceneObject::Torus(torus) => Some(torus.bounding_box()),
+            SceneObject::Capsule(capsule) => Some(capsule.bounding_box()),
+            SceneObject::ConstantMedium(medium) => medium.boundary.bounding_box(),
+            SceneObject::VoxelVolume(volume) => Some(Aabb::new(volume.min, volume.max)),
+            SceneObject::Transformed(transformed) => transformed.bounding_box(),
+            SceneObject::Csg(csg) => csg.bounding_box(),
+            SceneObject::Sdf(sdf) => Some(sdf.bounding_box()),
+            SceneObject::Instanced(instanced) => instanced.bounding_box(),
+        }
+    }
+
+    ///
+    /// Bytes used by the object's data outside of the scene node, geometry of instanced
+    /// objects is counted only when it isn't in `geometries` yet
+    ///
+    fn data_size(&self, geometries: &mut HashSet<*const Geometry>) -> usize {
+        match self {
+            SceneObject::Sphere(_) |
+            SceneObject::Cylinder(_) |
+            SceneObject::Cone(_) |
+            SceneObject::Disk(_) |
+            SceneObject::Torus(_) |
+            SceneObject::Capsule(_) => 0,
+            SceneObject::ConstantMedium(medium) => {
+                std::mem::size_of::<SceneObject>() + medium.boundary.data_size(geometries)
+            }
+            SceneObject::VoxelVolume(volume) => volume.grid.memory_size(),
+            SceneObject::Transformed(transformed) => {
+                std::mem::size_of::<SceneObject>() + transformed.object.data_size(geometries)
+            }
+            SceneObject::Csg(csg) => {
+                2 * std::mem::size_of::<SceneObject>() +
+                    csg.first.data_size(geometries) +
+                    csg.second.data_size(geometries)
+            }
+            SceneObject::Sdf(sdf) => sdf.data_size(),
+            SceneObject::Instanced(instanced) => instanced.data_size(geometries),
+        }
+    }
+
+    /// Add geometries of the instanced objects in this one to `geometries`, see [`Instanced::collect_geometries`]
+    fn collect_geometries(&self, geometries: &mut Vec<Arc<Geometry>>) {
+        match self {
+            SceneObject::ConstantMedium(medium) => medium.boundary.collect_geometries(geometries),
+            SceneObject::Transformed(transformed) => {
+                transformed.object.collect_geometries(geometries)
+            }
+            SceneObject::Csg(csg) => {
+                csg.first.collect_geometries(geometries);
+                csg.second.collect_geometries(geometries);
+            }
+            SceneObject::Instanced(instanced) => instanced.collect_geometries(geometries),
+            _ => {}
+        }
+    }
+}
+
+impl Hittable for SceneObject {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<Hit> {
+        match self {
+            SceneObject::Sphere(sphere) => sphere.hit(ray, t_min, t_max, sampler),
+            SceneObject::Cylinder(cylinder) => cylinder.hit(ray, t_min, t_max, sampler),
+            SceneObject::Cone(cone) => cone.hit(ray, t_min, t_max, sampler),
+            SceneObject::Disk(disk) => disk.hit(ray, t_min, t_max, sampler),
+            SceneObject::Torus(torus) => torus.hit(ray, t_min, t_max, sampler),
+            SceneObject::Capsule(capsule) => capsule.hit(ray, t_min, t_max, sampler),
+            SceneObject::ConstantMedium(medium) => medium.hit(ray, t_min, t_max, sampler),
+            SceneObject::VoxelVolume(volume) => volume.hit(ray, t_min, t_max, sampler),
+            SceneObject::Transformed(transformed) => transformed.hit(ray, t_min, t_max, sampler),
+            SceneObject::Csg(csg) => csg.hit(ray, t_min, t_max, sampler),
+            SceneObject::Sdf(sdf) => sdf.hit(ray, t_min, t_max, sampler),
+            SceneObject::Instanced(instanced) => instanced.hit(ray, t_min, t_max, sampler),
+        }
+    }
+
+    fn intersections(&self, ray: &Ray) -> Vec<Hit> {
+        match self {
+            SceneObject::Sphere(sphere) => sphere.intersections(ray),
+            SceneObject::Cylinder(cylinder) => cylinder.intersections(ray),
+            SceneObject::Cone(cone) => cone.intersections(ray),
+            SceneObject::Disk(disk) => disk.intersections(ray),
+            SceneObject::Torus(torus) => torus.intersections(ray),
+            SceneObject::Capsule(capsule) => capsule.intersections(ray),
+            SceneObject::ConstantMedium(medium) => medium.intersections(ray),
+            SceneObject::VoxelVolume(volume) => volume.intersections(ray),
+            SceneObject::Transformed(transformed) => transformed.intersections(ray),
+            SceneObject::Csg(csg) => csg.intersections(ray),
+            SceneObject::Sdf(sdf) => sdf.intersections(ray),
+            SceneObject::Instanced(instanced) => instanced.intersections(ray),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            SceneObject::Sphere(sphere) => sphere.name(),
+            SceneObject::Cylinder(cylinder) => cylinder.name(),
+            SceneObject::Cone(cone) => cone.name(),
+            SceneObject::Disk(disk) => disk.name(),
+            SceneObject::Torus(torus) => torus.name(),
+            SceneObject::Capsule(capsule) => capsule.name(),
+            SceneObject::ConstantMedium(medium) => medium.name(),
+            SceneObject::VoxelVolume(volume) => volume.name(),
+            SceneObject::Transformed(transformed) => transformed.name(),
+            SceneObject::Csg(csg) => csg.name(),
+            SceneObject::Sdf(sdf) => sdf.name(),
+            SceneObject::Instanced(instanced) => instanced.name(),
+        }
+    }
+}
+
+///
+/// Types of objects which can be added to the scene
+///
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ObjectType {
+    Sphere,
+    Cylinder,
+    Cone,
+    Disk,
+    Torus,
+    Capsule,
+    ConstantMedium,
+    VoxelVolume,
+    Transformed,
+    Csg,
+    Sdf,
+    Instanced,
+}
+
+impl ObjectType {
+    pub const ALL: [ObjectType; 12] = [
+        ObjectType::Sphere,
+        ObjectType::Cylinder,
+        ObjectType::Cone,
+        ObjectType::Disk,
+        ObjectType::Torus,
+        ObjectType::Capsule,
+        ObjectType::ConstantMedium,
+        ObjectType::VoxelVolume,
+        ObjectType::Transformed,
+        ObjectType::Csg,
+        ObjectType::Sdf,
+        ObjectType::Instanced,
+    ];
+
+    /// Create object of this type with default parameters, placed in front of the camera
+    pub fn create(&self) -> SceneObject {
+        match self {
+            ObjectType::Sphere => {
+                SceneObject::Sphere(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5))
+            }
+            ObjectType::Cylinder => {
+                SceneObject::Cylinder(Cylinder::new(Point3::new(0.0, 0.0, -1.0), 0.4, 0.8))
+            }
+            ObjectType::Cone => {
+                SceneObject::Cone(Cone::new(Point3::new(0.0, -0.4, -1.0), 0.4, 0.8))
+            }
+            ObjectType::Disk => SceneObject::Disk(Disk::new(Point3::new(0.0, -0.5, -1.0), 0.5)),
+            ObjectType::Torus => {
+                SceneObject::Torus(Torus::new(Point3::new(0.0, 0.0, -1.0), 0.4, 0.15))
+            }
+            ObjectType::Capsule => {
+                SceneObject::Capsule(Capsule::new(Point3::new(0.0, 0.0, -1.0), 0.25, 0.5))
+            }
+            ObjectType::ConstantMedium => SceneObject::ConstantMedium(ConstantMedium::new(
+                SceneObject::Sphere(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5)),
+                2.0,
+            )),
+            ObjectType::VoxelVolume => SceneObject::VoxelVolume(VoxelVolume::new(
+                Point3::new(0.0, 0.0, -1.0),
+                1.0,
+                VoxelGrid::new(VoxelSource::default()).expect("Noise grid can't fail to generate"),
+            )),
+            ObjectType::Transformed => SceneObject::Transformed(Transformed::new(
+                SceneObject::Sphere(Sphere::new(Point3::new(0.0, 0.0, 0.0), 0.5)),
+                Transform {
+                    translation: Vec3::new(0.0, 0.0, -1.0),
+                    ..Transform::default()
+                },
+            )),
+            ObjectType::Csg => SceneObject::Csg(Csg::new(
+                CsgOperation::Difference,
+                SceneObject::Sphere(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5)),
+                SceneObject::Sphere(Sphere::new(Point3::new(0.3, 0.2, -0.7), 0.35)),
+            )),
+            ObjectType::Sdf => SceneObject::Sdf(SdfObject::new(
+                Point3::new(0.0, 0.0, -1.0),
+                0.7,
+                SdfShape::SmoothUnion {
+                    first: Box::new(SdfShape::Sphere { radius: 0.25 }),
+                    second: Box::new(SdfShape::Displace {
+                        amplitude: 0.02,
+                        frequency: 30.0,
+                        shape: Box::new(SdfShape::Torus {
+                            major_radius: 0.4,
+                            minor_radius: 0.1,
+                        }),
+                    }),
+                    smoothness: 0.15,
+                },
+            )),
+            ObjectType::Instanced => {
+                // grid of small spheres on the ground, like the book's final scene
+                let instances = (0..100)
+                    .map(|index| {
+                        let (column, row) = ((index % 10) as f64, (index / 10) as f64);
+                        Instance {
+                            transform: Transform {
+                                translation: Vec3::new(
+                                    0.25 * column - 1.125,
+                                    -0.42,
+                                    0.25 * row - 2.5,
+                                ),
+                                rotation: Vec3::splat(0.0),
+                                scale: 0.08,
+                            },
+                            color: Some(Color3::new(0.3 + 0.06 * column, 0.5, 0.3 + 0.06 * row)),
+                            material: None,
+                        }
+                    })
+                    .collect();
+                SceneObject::Instanced(Instanced::new(
+                    vec![SceneObject::Sphere(Sphere::new(
+                        Point3::new(0.0, 0.0, 0.0),
+                        1.0,
+                    ))],
+                    instances,
+                ))
+            }
+        }
+    }
+}
+
+impl Display for ObjectType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ObjectType::Sphere => "Sphere",
+            ObjectType::Cylinder => "Cylinder",
+            ObjectType::Cone => "Cone",
+            ObjectType::Disk => "Disk",
+            ObjectType::Torus => "Torus",
+            ObjectType::Capsule => "Capsule",
+            ObjectType::ConstantMedium => "Fog volume",
+            ObjectType::VoxelVolume => "Voxel volume",
+            ObjectType::Transformed => "Transformed object",
+            ObjectType::Csg => "CSG",
+            ObjectType::Sdf => "SDF shape",
+            ObjectType::Instanced => "Instances",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+///
+/// Entry of the scene outliner, an object with its user given name and visibility
+///
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SceneNode {
+    pub name: String,
+    pub visible: bool,
+    /// Shared by clones of the scene, edits replace it with a new one
+    #[serde(with = "shared_object")]
+    pub object: Arc<SceneObject>,
+    #[serde(default)]
+    pub tracks: ObjectTracks,
+}
+
+impl SceneNode {
+    pub fn new(name: &str, object: SceneObject) -> Self {
+        Self {
+            name: name.to_string(),
+            visible: true,
+            object: Arc::new(object),
+            tracks: ObjectTracks::default(),
+        }
+    }
+}
+
+impl PartialEq for SceneNode {
+    fn eq(&self, other: &Self) -> bool {
+        // shared objects are equal without comparing their data
+        self.name == other.name &&
+            self.visible == other.visible &&
+            self.tracks == other.tracks &&
+            (Arc::ptr_eq(&self.object, &other.object) || self.object == other.object)
+    }
+}
+
+/// Serializes the shared object like an owned one
+mod shared_object {
+    use crate::renderer::scene::SceneObject;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::sync::Arc;
+
+    pub fn serialize<S: Serializer>(object: &Arc<SceneObject>, s: S) -> Result<S::Ok, S::Error> {
+        object.as_ref().serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Arc<SceneObject>, D::Error> {
+        SceneObject::deserialize(d).map(Arc::new)
+    }
 }
 
 pub struct Scene {
-    pub(crate) contents: Vec<SceneObject>,
+    contents: Vec<SceneNode>,
+    pub animation: Animation,
+    /// Hierarchy over the visible objects, built by the first ray after the contents changed
+    bvh: OnceLock<SceneBvh>,
+}
+
+///
+/// Serialized form of the scene, geometries shared by instanced objects are stored once in
+/// a table before the objects using them
+///
+#[derive(Serialize, Deserialize)]
+struct SceneData<'a> {
+    #[serde(default)]
+    geometries: Vec<SharedGeometry>,
+    contents: Cow<'a, [SceneNode]>,
+    #[serde(default)]
+    animation: Cow<'a, Animation>,
+}
+
+struct SceneBvh {
+    /// Indices of the visible nodes, the items of the hierarchy
+    nodes: Vec<usize>,
+    bvh: Bvh,
+}
+
+impl Scene {
+    pub fn contents(&self) -> &[SceneNode] {
+        &self.contents
+    }
+
+    /// Objects for editing, the hierarchy gets rebuilt when the scene is hit next
+    pub fn contents_mut(&mut self) -> &mut Vec<SceneNode> {
+        self.bvh.take();
+        &mut self.contents
+    }
+
+    fn bvh(&self) -> &SceneBvh {
+        self.bvh.get_or_init(|| {
+            let nodes: Vec<usize> = (0..self.contents.len())
+                .filter(|index| self.contents[*index].visible)
+                .collect();
+            let bounds: Vec<Option<Aabb>> = nodes
+                .iter()
+                .map(|index| self.contents[*index].object.bounding_box())
+                .collect();
+            SceneBvh {
+                nodes,
+                bvh: Bvh::build(&bounds),
+            }
+        })
+    }
+
+    ///
+    /// Add new object of the given type, returns its index
+    ///
+    pub fn add(&mut self, object_type: ObjectType) -> usize {
+        let count = self
+            .contents
+            .iter()
+            .filter(|node| node.object.object_type() == object_type)
+            .count();
+        let name = format!("{} {}", object_type, count + 1);
+        self.contents_mut()
+            .push(SceneNode::new(&name, object_type.create()));
+        self.contents.len() - 1
+    }
+
+    /// Approximate number of bytes used by the scene
+    pub fn memory_size(&self) -> usize {
+        let mut geometries = HashSet::new();
+        std::mem::size_of::<Scene>() +
+            self.contents.capacity() * std::mem::size_of::<SceneNode>() +
+            self.contents
+                .iter()
+                .map(|node| node.name.capacity() + node.object.data_size(&mut geometries))
+                .sum::<usize>() +
+            self.bvh().bvh.memory_size()
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.contents_mut().remove(index);
+    }
+
+    /// Keys of the object, editing them keeps the hierarchy as objects move only in `set_frame`
+    pub fn tracks_mut(&mut self, index: usize) -> Option<&mut ObjectTracks> {
+        self.contents.get_mut(index).map(|node| &mut node.tracks)
+    }
+
+    ///
+    /// Insert copy of the object right after the original, returns index of the copy
+    ///
+    pub fn duplicate(&mut self, index: usize) -> usize {
+        let mut copy = self.contents[index].clone();
+        copy.name = format!("{} copy", copy.name);
+        self.contents_mut().insert(index + 1, copy);
+        index + 1
+    }
+
+    ///
+    /// Swap object with its neighbour, `offset` is -1 (up) or 1 (down), returns the new index
+    ///
+    pub fn move_object(&mut self, index: usize, offset: isize) -> usize {
+        let target = index as isize + offset;
+        if target < 0 || target >= self.contents.len() as isize {
+            return index;
+        }
+        self.contents_mut().swap(index, target as usize);
+        target as usize
+    }
+
+    ///
+    /// Pose objects and the camera as keyframed for the frame, properties without keys
+    /// keep their values
+    ///
+    pub fn set_frame(&mut self, frame: u32, camera: &mut CameraView) {
+        self.animation.current = frame;
+        if let Some(view) = self.animation.camera.sample(frame as f64) {
+            *camera = view;
+        }
+        for node in self.contents_mut() {
+            let tracks = &node.tracks;
+            // objects without keys stay shared with earlier clones of the scene
+            if let Some(position) = tracks.position.sample(frame as f64) {
+                // the shutter interval spans one frame
+                let next_position = tracks.position.sample(frame as f64 + 1.0);
+                Arc::make_mut(&mut node.object)
+                    .set_position(position, next_position.unwrap_or(position));
+            }
+            if let Some(color) = tracks.color.sample(frame as f64) {
+                Arc::make_mut(&mut node.object).set_color(color);
+            }
+        }
+    }
 }
 
 impl Clone for Scene {
     fn clone(&self) -> Self {
         Self {
-            contents: self.contents.iter().cloned().collect(),
+            contents: self.contents.to_vec(),
+            animation: self.animation.clone(),
+            bvh: OnceLock::new(),
+        }
+    }
+}
+
+impl Serialize for Scene {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut geometries = vec![];
+        for node in &self.contents {
+            node.object.collect_geometries(&mut geometries);
         }
+        let data = SceneData {
+            geometries: geometries.iter().cloned().map(SharedGeometry).collect(),
+            contents: Cow::Borrowed(&self.contents),
+            animation: Cow::Borrowed(&self.animation),
+        };
+        with_geometry_table(geometries, || data.serialize(s))
+    }
+}
+
+impl<'de> Deserialize<'de> for Scene {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let data = with_geometry_table(vec![], || SceneData::deserialize(d))?;
+        Ok(Self {
+            contents: data.contents.into_owned(),
+            animation: data.animation.into_owned(),
+            bvh: OnceLock::new(),
+        })
+    }
+}
+
+impl PartialEq for Scene {
+    fn eq(&self, other: &Self) -> bool {
+        self.contents == other.contents && self.animation == other.animation
     }
 }
 
 impl Hittable for Scene {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
-        let mut closest_so_far = t_max;
-        let mut closest_hit: Option<Hit> = None;
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<Hit> {
+        let SceneBvh { nodes, bvh } = self.bvh();
+        bvh.closest(ray, t_min, t_max, |item, t_max| {
+            let index = nodes[item];
+            let mut hit = self.contents[index]
+                .object
+                .hit(ray, t_min, t_max, sampler)?;
+            hit.object = index;
+            Some((hit.t, hit))
+        })
+    }
 
-        for object in &self.contents {
-            match object {
-                SceneObject::Sphere(sphere) => {
-                    if let Some(hit) = sphere.hit(ray, t_min, closest_so_far) {
-                        closest_so_far = hit.t;
-                        closest_hit = Some(hit)
-                    }
-                }
-            }
+    fn intersections(&self, ray: &Ray) -> Vec<Hit> {
+        let SceneBvh { nodes, bvh } = self.bvh();
+        let mut hits = vec![];
+        for item in bvh.candidates(ray) {
+            let index = nodes[item];
+            hits.extend(
+                self.contents[index]
+                    .object
+                    .intersections(ray)
+                    .into_iter()
+                    .map(|mut hit| {
+                        hit.object = index;
+                        hit
+                    }),
+            );
         }
-        return closest_hit;
+        hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+        hits
     }
 
     fn name(&self) -> String {
@@ -49,9 +716,17 @@ impl Default for Scene {
     fn default() -> Self {
         Self {
             contents: vec![
-                SceneObject::Sphere(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5)),
-                SceneObject::Sphere(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0)),
+                SceneNode::new(
+                    "Sphere",
+                    SceneObject::Sphere(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5)),
+                ),
+                SceneNode::new(
+                    "Ground",
+                    SceneObject::Sphere(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0)),
+                ),
             ],
+            animation: Animation::default(),
+            bvh: OnceLock::new(),
         }
     }
 }
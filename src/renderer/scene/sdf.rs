@@ -0,0 +1,266 @@
+use crate::math::{solve_quadratic, Point3};
+use crate::renderer::bvh::Aabb;
+use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::sampler::Sampler;
+use crate::{Color3, Ray, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Marching stops when the surface is closer than this
+const SURFACE_DISTANCE: f64 = 1e-4;
+const MAX_STEPS: usize = 512;
+/// Offset of the samples used to estimate the gradient
+const NORMAL_OFFSET: f64 = 1e-4;
+
+///
+/// Shape described by its signed distance function: negative inside, positive outside.
+/// Shapes are centered at the origin, [`SdfShape::Translate`] moves them.
+///
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum SdfShape {
+    Sphere {
+        radius: f64,
+    },
+    Box {
+        half_size: Vec3,
+    },
+    /// Ring in the xz plane
+    Torus {
+        major_radius: f64,
+        minor_radius: f64,
+    },
+    Translate {
+        offset: Vec3,
+        shape: Box<SdfShape>,
+    },
+    /// Union blending the shapes together where they are closer than `smoothness`
+    SmoothUnion {
+        first: Box<SdfShape>,
+        second: Box<SdfShape>,
+        smoothness: f64,
+    },
+    /// Infinite copies of the shape, zero period leaves the axis unrepeated
+    Repeat {
+        period: Vec3,
+        shape: Box<SdfShape>,
+    },
+    /// Surface moved along its normal by a sine pattern
+    Displace {
+        amplitude: f64,
+        frequency: f64,
+        shape: Box<SdfShape>,
+    },
+}
+
+impl SdfShape {
+    pub fn distance(&self, p: &Point3) -> f64 {
+        match self {
+            SdfShape::Sphere { radius } => p.length() - radius,
+            SdfShape::Box { half_size } => {
+                let q = Vec3::from([0, 1, 2].map(|i| p.data[i].abs() - half_size.data[i]));
+                let outside = Vec3::from(q.data.map(|c| c.max(0.0))).length();
+                let inside = q.x().max(q.y()).max(q.z()).min(0.0);
+                outside + inside
+            }
+            SdfShape::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                let ring_distance = (p.x() * p.x() + p.z() * p.z()).sqrt() - major_radius;
+                (ring_distance * ring_distance + p.y() * p.y()).sqrt() - minor_radius
+            }
+            SdfShape::Translate { offset, shape } => shape.distance(&(p - offset)),
+            SdfShape::SmoothUnion {
+                first,
+                second,
+                smoothness,
+            } => {
+                // polynomial smooth minimum
+                let d1 = first.distance(p);
+                let d2 = second.distance(p);
+                let k = smoothness.max(1e-9);
+                let h = (0.5 + 0.5 * (d2 - d1) / k).clamp(0.0, 1.0);
+                d2 * (1.0 - h) + d1 * h - k * h * (1.0 - h)
+            }
+            SdfShape::Repeat { period, shape } => {
+                let local = Vec3::from([0, 1, 2].map(|i| match period.data[i] > 0.0 {
+                    true => p.data[i] - period.data[i] * (p.data[i] / period.data[i]).round(),
+                    false => p.data[i],
+                }));
+                shape.distance(&local)
+            }
+            SdfShape::Displace {
+                amplitude,
+                frequency,
+                shape,
+            } => {
+                let displacement = amplitude *
+                    (frequency * p.x()).sin() *
+                    (frequency * p.y()).sin() *
+                    (frequency * p.z()).sin();
+                // the displacement makes the field steeper than a distance, scaling it back
+                // keeps the marching steps conservative
+                let steepness = 1.0 + amplitude.abs() * frequency.abs() * 3f64.sqrt();
+                (shape.distance(p) + displacement) / steepness
+            }
+        }
+    }
+
+    /// Shapes this one is built from
+    pub fn children_mut(&mut self) -> Vec<&mut SdfShape> {
+        match self {
+            SdfShape::Sphere { .. } | SdfShape::Box { .. } | SdfShape::Torus { .. } => vec![],
+            SdfShape::Translate { shape, .. } |
+            SdfShape::Repeat { shape, .. } |
+            SdfShape::Displace { shape, .. } => vec![shape],
+            SdfShape::SmoothUnion { first, second, .. } => vec![first, second],
+        }
+    }
+
+    fn node_count(&self) -> usize {
+        match self {
+            SdfShape::Sphere { .. } | SdfShape::Box { .. } | SdfShape::Torus { .. } => 1,
+            SdfShape::Translate { shape, .. } |
+            SdfShape::Repeat { shape, .. } |
+            SdfShape::Displace { shape, .. } => 1 + shape.node_count(),
+            SdfShape::SmoothUnion { first, second, .. } => {
+                1 + first.node_count() + second.node_count()
+            }
+        }
+    }
+
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            SdfShape::Sphere { .. } => "Sphere",
+            SdfShape::Box { .. } => "Box",
+            SdfShape::Torus { .. } => "Torus",
+            SdfShape::Translate { .. } => "Translate",
+            SdfShape::SmoothUnion { .. } => "Smooth union",
+            SdfShape::Repeat { .. } => "Repeat",
+            SdfShape::Displace { .. } => "Displace",
+        }
+    }
+}
+
+///
+/// Object rendered by sphere tracing its distance function, clipped to the bounding sphere
+/// of `bound_radius` around the center
+///
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct SdfObject {
+    pub center: Point3,
+    pub bound_radius: f64,
+    pub shape: SdfShape,
+    pub color: Color3,
+}
+
+impl SdfObject {
+    pub fn new(center: Point3, bound_radius: f64, shape: SdfShape) -> Self {
+        Self {
+            center,
+            bound_radius,
+            shape,
+            color: Color3::splat(1.0),
+        }
+    }
+
+    fn distance(&self, point: &Point3) -> f64 {
+        self.shape.distance(&(point - &self.center))
+    }
+
+    /// Bytes used by the shape tree
+    pub fn data_size(&self) -> usize {
+        self.shape.node_count() * std::mem::size_of::<SdfShape>()
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        Aabb::around_sphere(self.center, self.bound_radius)
+    }
+
+    /// Ray parameters where the ray enters and leaves the bounding sphere
+    fn bounds(&self, ray: &Ray) -> Option<[f64; 2]> {
+        let oc = ray.origin() - &self.center;
+        match solve_quadratic(
+            ray.direction().length_squared(),
+            2.0 * Vec3::dot(&oc, ray.direction()),
+            oc.length_squared() - self.bound_radius * self.bound_radius,
+        )[..]
+        {
+            [entry, exit] => Some([entry, exit]),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Step along the ray from `start` by the distance to the surface until it gets close
+    /// enough, the absolute distance lets the march start inside the shape too
+    ///
+    fn march(&self, ray: &Ray, start: f64, end: f64) -> Option<f64> {
+        let speed = ray.direction().length();
+        let mut t = start;
+        for _ in 0..MAX_STEPS {
+            if t > end {
+                return None;
+            }
+            let distance = self.distance(&ray.at(t)).abs();
+            if distance < SURFACE_DISTANCE {
+                return Some(t);
+            }
+            t += distance / speed;
+        }
+        None
+    }
+
+    fn hit_at(&self, ray: &Ray, t: f64) -> Hit {
+        let point = ray.at(t);
+        // gradient from the tetrahedron of samples around the point
+        let outward_normal = [
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ]
+        .into_iter()
+        .fold(Vec3::splat(0.0), |gradient, corner| {
+            gradient + corner * self.distance(&(point + corner * NORMAL_OFFSET))
+        })
+        .normalized();
+        // spherical projection around the center
+        let direction = (point - self.center).normalized();
+        let uv = [
+            ((-direction.z()).atan2(direction.x()) + PI) / (2.0 * PI),
+            (-direction.y()).clamp(-1.0, 1.0).acos() / PI,
+        ];
+        Hit::diffuse(ray, t, outward_normal, uv, self.color)
+    }
+}
+
+impl Hittable for SdfObject {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _sampler: &mut dyn Sampler) -> Option<Hit> {
+        let [entry, exit] = self.bounds(ray)?;
+        self.march(ray, entry.max(t_min), exit.min(t_max))
+            .map(|t| self.hit_at(ray, t))
+    }
+
+    fn intersections(&self, ray: &Ray) -> Vec<Hit> {
+        let Some([entry, exit]) = self.bounds(ray) else {
+            return vec![];
+        };
+        let mut t = entry;
+        let escape_step = 2.0 * SURFACE_DISTANCE / ray.direction().length();
+        let mut hits = vec![];
+        while let Some(surface) = self.march(ray, t, exit) {
+            hits.push(self.hit_at(ray, surface));
+            // get away from the surface before marching to the next one
+            t = surface + escape_step;
+            while t <= exit && self.distance(&ray.at(t)).abs() < SURFACE_DISTANCE {
+                t += escape_step;
+            }
+        }
+        hits
+    }
+
+    fn name(&self) -> String {
+        format!("SDF {} at {}", self.shape.kind_name(), self.center)
+    }
+}
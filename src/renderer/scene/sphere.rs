@@ -1,13 +1,20 @@
 use crate::math::Point3;
+use crate::renderer::bvh::Aabb;
 use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::sampler::Sampler;
 use crate::{Color3, Ray, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Sphere {
     pub(crate) center: Point3,
     pub(crate) radius: f64,
     pub max_radius: f64,
     pub color: Color3,
+    /// Center at time 1 of a moving sphere, it moves linearly from `center` at time 0
+    #[serde(default)]
+    pub end_center: Option<Point3>,
 }
 
 impl Sphere {
@@ -17,50 +24,64 @@ impl Sphere {
             radius,
             max_radius: 2.0 * radius,
             color: Color3::splat(1.0),
+            end_center: None,
         }
     }
-}
 
-impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
-        let oc = ray.origin() - &self.center;
+    /// Parameters of both points where the ray crosses the sphere, nearest first
+    fn roots(&self, ray: &Ray) -> Option<[f64; 2]> {
+        let oc = ray.origin() - &self.center(ray.time());
         let a = ray.direction().length_squared();
         let half_b = Vec3::dot(&oc, ray.direction());
         let c = oc.length_squared() - self.radius * self.radius;
 
         let discriminant = half_b * half_b - a * c;
-        return if discriminant < 0.0 {
-            None
-        } else {
-            let sqrtd = discriminant.sqrt();
+        if discriminant < 0.0 {
+            return None;
+        }
+        let sqrtd = discriminant.sqrt();
+        Some([(-half_b - sqrtd) / a, (-half_b + sqrtd) / a])
+    }
 
-            // Find the nearest root that lies in the acceptable range.
-            let mut root = (-half_b - sqrtd) / a;
-            if root < t_min || t_max < root {
-                root = (-half_b + sqrtd) / a;
-                if root < t_min || t_max < root {
-                    return None;
-                }
-            }
+    fn hit_at(&self, ray: &Ray, t: f64) -> Hit {
+        let outward_normal = (ray.at(t) - self.center(ray.time())) / self.radius;
+        // longitude from -x around through +z, latitude from the bottom pole
+        let uv = [
+            ((-outward_normal.z()).atan2(outward_normal.x()) + PI) / (2.0 * PI),
+            (-outward_normal.y()).clamp(-1.0, 1.0).acos() / PI,
+        ];
+        Hit::diffuse(ray, t, outward_normal, uv, self.color)
+    }
 
-            let hit_point = ray.at(root);
-            let outward_normal = (hit_point - self.center) / self.radius;
+    /// Bounds of the sphere along its whole motion
+    pub fn bounding_box(&self) -> Aabb {
+        Aabb::around_sphere(self.center(0.0), self.radius)
+            .union(&Aabb::around_sphere(self.center(1.0), self.radius))
+    }
 
-            let front_face = Vec3::dot(ray.direction(), &outward_normal) < 0.0;
-            let normal = match front_face {
-                true => outward_normal,
-                false => -outward_normal,
-            };
+    /// Center at the given time
+    pub fn center(&self, time: f64) -> Point3 {
+        match self.end_center {
+            Some(end_center) => end_center.lerp(time, &self.center),
+            None => self.center,
+        }
+    }
+}
+
+impl Hittable for Sphere {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _sampler: &mut dyn Sampler) -> Option<Hit> {
+        // Find the nearest root that lies in the acceptable range.
+        self.roots(ray)?
+            .into_iter()
+            .find(|root| (t_min..=t_max).contains(root))
+            .map(|root| self.hit_at(ray, root))
+    }
 
-            let hit_record = Hit {
-                point: hit_point,
-                normal,
-                front_face,
-                t: root,
-                surface: self.color,
-            };
-            return Some(hit_record);
-        };
+    fn intersections(&self, ray: &Ray) -> Vec<Hit> {
+        match self.roots(ray) {
+            Some(roots) => roots.iter().map(|root| self.hit_at(ray, *root)).collect(),
+            None => vec![],
+        }
     }
 
     fn name(&self) -> String {
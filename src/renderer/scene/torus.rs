@@ -0,0 +1,95 @@
+use crate::math::{solve_quartic, Point3};
+use crate::renderer::bvh::Aabb;
+use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::sampler::Sampler;
+use crate::{Color3, Ray, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+///
+/// Ring lying in the xz plane: points at `minor_radius` from the circle of `major_radius`
+/// around the center
+///
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Torus {
+    pub center: Point3,
+    pub major_radius: f64,
+    pub minor_radius: f64,
+    pub color: Color3,
+}
+
+impl Torus {
+    pub fn new(center: Point3, major_radius: f64, minor_radius: f64) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+            color: Color3::splat(1.0),
+        }
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        let ring = self.major_radius + self.minor_radius;
+        let extent = Vec3::new(ring, self.minor_radius, ring);
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+
+    /// Parameters of all points where the ray crosses the surface, nearest first
+    fn roots(&self, ray: &Ray) -> Vec<f64> {
+        // the quartic is solved for a unit direction to keep its coefficients in a sane range
+        let length = ray.direction().length();
+        let d = *ray.direction() / length;
+        let o = *ray.origin() - self.center;
+        let major2 = self.major_radius * self.major_radius;
+
+        // (|p|² + R² - r²)² = 4 R² (x² + z²) for p = o + s d
+        let od = Vec3::dot(&o, &d);
+        let k = o.length_squared() + major2 - self.minor_radius * self.minor_radius;
+        solve_quartic(
+            1.0,
+            4.0 * od,
+            4.0 * od * od + 2.0 * k - 4.0 * major2 * (d.x() * d.x() + d.z() * d.z()),
+            4.0 * od * k - 8.0 * major2 * (o.x() * d.x() + o.z() * d.z()),
+            k * k - 4.0 * major2 * (o.x() * o.x() + o.z() * o.z()),
+        )
+        .into_iter()
+        .map(|s| s / length)
+        .collect()
+    }
+
+    fn hit_at(&self, ray: &Ray, t: f64) -> Hit {
+        let local = ray.at(t) - self.center;
+        // the normal points away from the nearest point of the core circle
+        let ring_distance = (local.x() * local.x() + local.z() * local.z()).sqrt();
+        let core = match ring_distance > 0.0 {
+            true => Vec3::new(local.x(), 0.0, local.z()) * (self.major_radius / ring_distance),
+            false => Vec3::new(self.major_radius, 0.0, 0.0),
+        };
+        let outward_normal = (local - core).normalized();
+        let uv = [
+            (local.z().atan2(local.x()) + PI) / (2.0 * PI),
+            (local.y().atan2(ring_distance - self.major_radius) + PI) / (2.0 * PI),
+        ];
+        Hit::diffuse(ray, t, outward_normal, uv, self.color)
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _sampler: &mut dyn Sampler) -> Option<Hit> {
+        self.roots(ray)
+            .into_iter()
+            .find(|t| (t_min..=t_max).contains(t))
+            .map(|t| self.hit_at(ray, t))
+    }
+
+    fn intersections(&self, ray: &Ray) -> Vec<Hit> {
+        self.roots(ray)
+            .into_iter()
+            .map(|t| self.hit_at(ray, t))
+            .collect()
+    }
+
+    fn name(&self) -> String {
+        format!("Torus at {}", self.center)
+    }
+}
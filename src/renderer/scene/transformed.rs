@@ -0,0 +1,163 @@
+use crate::math::Point3;
+use crate::renderer::bvh::Aabb;
+use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::sampler::Sampler;
+use crate::renderer::scene::SceneObject;
+use crate::{Ray, Vec3};
+use serde::{Deserialize, Serialize};
+
+///
+/// Uniform scale, then rotation around x, y and z (in degrees), then translation
+///
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Vec3,
+    pub scale: f64,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vec3::splat(0.0),
+            rotation: Vec3::splat(0.0),
+            scale: 1.0,
+        }
+    }
+}
+
+impl Transform {
+    /// Transform between `self` at time 0 and `end` at time 1
+    pub fn interpolate(&self, end: &Transform, time: f64) -> Transform {
+        Transform {
+            translation: end.translation.lerp(time, &self.translation),
+            rotation: end.rotation.lerp(time, &self.rotation),
+            scale: self.scale + (end.scale - self.scale) * time,
+        }
+    }
+
+    /// Ray in the space of the transformed object, with the same parametrization
+    pub(crate) fn local_ray(&self, ray: &Ray) -> Option<Ray> {
+        if self.scale == 0.0 {
+            return None;
+        }
+        let origin = self.rotate(*ray.origin() - self.translation, true) / self.scale;
+        let direction = self.rotate(*ray.direction(), true) / self.scale;
+        Some(Ray::new(origin, direction, ray.time()))
+    }
+
+    /// Direction of the normal after the transform, the scale is uniform so only the
+    /// rotation changes it
+    pub(crate) fn normal_to_world(&self, normal: Vec3) -> Vec3 {
+        self.rotate(normal, false)
+    }
+
+    pub(crate) fn apply(&self, point: Point3) -> Point3 {
+        self.rotate(point * self.scale, false) + self.translation
+    }
+
+    /// Box containing the transformed box
+    pub(crate) fn bounds(&self, bounds: &Aabb) -> Aabb {
+        Aabb::around_points(bounds.corners().map(|corner| self.apply(corner)))
+    }
+
+    fn rotate(&self, v: Vec3, inverse: bool) -> Vec3 {
+        let mut data = v.data;
+        let mut axes = [0, 1, 2];
+        if inverse {
+            axes.reverse();
+        }
+        for axis in axes {
+            let angle = match inverse {
+                true => -self.rotation.data[axis],
+                false => self.rotation.data[axis],
+            }
+            .to_radians();
+            let (sin, cos) = angle.sin_cos();
+            let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+            let (x, y) = (data[a], data[b]);
+            data[a] = cos * x - sin * y;
+            data[b] = sin * x + cos * y;
+        }
+        Vec3::from(data)
+    }
+}
+
+///
+/// Object placed by a transform which can change during the shutter interval, the motion
+/// is linear from `start` at time 0 to `end` at time 1
+///
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transformed {
+    pub object: Box<SceneObject>,
+    pub start: Transform,
+    pub end: Transform,
+}
+
+impl Transformed {
+    pub fn new(object: SceneObject, transform: Transform) -> Self {
+        Self {
+            object: Box::new(object),
+            start: transform,
+            end: transform,
+        }
+    }
+
+    ///
+    /// Transform at the ray's time and the ray in object space, it keeps the same
+    /// parametrization so distances stay comparable
+    ///
+    fn local_ray(&self, ray: &Ray) -> Option<(Transform, Ray)> {
+        let transform = self.start.interpolate(&self.end, ray.time());
+        let local_ray = transform.local_ray(ray)?;
+        Some((transform, local_ray))
+    }
+
+    fn to_world(transform: &Transform, ray: &Ray, mut hit: Hit) -> Hit {
+        hit.point = ray.at(hit.t);
+        hit.normal = transform.normal_to_world(hit.normal);
+        hit
+    }
+
+    /// Bounds covering the object during the whole shutter interval
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        let bounds = self.object.bounding_box()?;
+        if self.start.rotation == self.end.rotation {
+            // without rotation changes the corners move along straight lines
+            return Some(self.start.bounds(&bounds).union(&self.end.bounds(&bounds)));
+        }
+        // rotating object stays within the sphere reaching its farthest corner
+        let radius = bounds
+            .corners()
+            .iter()
+            .map(|corner| corner.length())
+            .fold(0.0, f64::max);
+        let [start, end] = [self.start, self.end]
+            .map(|transform| Aabb::around_sphere(transform.translation, radius * transform.scale));
+        Some(start.union(&end))
+    }
+}
+
+impl Hittable for Transformed {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<Hit> {
+        let (transform, local_ray) = self.local_ray(ray)?;
+        let hit = self.object.hit(&local_ray, t_min, t_max, sampler)?;
+        Some(Self::to_world(&transform, ray, hit))
+    }
+
+    fn intersections(&self, ray: &Ray) -> Vec<Hit> {
+        match self.local_ray(ray) {
+            Some((transform, local_ray)) => self
+                .object
+                .intersections(&local_ray)
+                .into_iter()
+                .map(|hit| Self::to_world(&transform, ray, hit))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("Transformed {}", self.object.name())
+    }
+}
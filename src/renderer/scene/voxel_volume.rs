@@ -0,0 +1,338 @@
+use crate::math::Point3;
+use crate::renderer::hittable::{Hit, Hittable};
+use crate::renderer::material::Material;
+use crate::renderer::sampler::{hash, Sampler};
+use crate::{Color3, Ray, Vec3};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+///
+/// Where the densities of a voxel grid come from
+///
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum VoxelSource {
+    /// Fractal value noise fading out towards the border of the grid
+    Noise {
+        resolution: usize,
+        frequency: f64,
+        octaves: u32,
+        seed: u32,
+    },
+    /// File with one byte per voxel, x varies fastest, then y, then z
+    RawFile { path: PathBuf, size: [usize; 3] },
+}
+
+impl Default for VoxelSource {
+    fn default() -> Self {
+        VoxelSource::Noise {
+            resolution: 48,
+            frequency: 4.0,
+            octaves: 4,
+            seed: 1,
+        }
+    }
+}
+
+impl Display for VoxelSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoxelSource::Noise { resolution, .. } => write!(f, "Noise {}³", resolution),
+            VoxelSource::RawFile { path, size } => {
+                write!(f, "{} {}x{}x{}", path.display(), size[0], size[1], size[2])
+            }
+        }
+    }
+}
+
+///
+/// Densities in [0, 1] on a regular grid, the data is shared between copies of the scene.
+/// Only the source is saved with the scene, the densities are rebuilt when it's loaded.
+/// A source that fails to load (like a missing file) gives an empty grid keeping the error,
+/// so the rest of the scene still loads.
+///
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "VoxelSource", into = "VoxelSource")]
+pub struct VoxelGrid {
+    source: VoxelSource,
+    size: [usize; 3],
+    densities: Arc<Vec<f32>>,
+    max_density: f64,
+    error: Option<String>,
+}
+
+impl VoxelGrid {
+    pub fn new(source: VoxelSource) -> io::Result<Self> {
+        let (size, densities) = match &source {
+            VoxelSource::Noise {
+                resolution,
+                frequency,
+                octaves,
+                seed,
+            } => {
+                let size = [(*resolution).max(2); 3];
+                (size, noise_grid(size, *frequency, *octaves, *seed))
+            }
+            VoxelSource::RawFile { path, size } => {
+                let bytes = std::fs::read(path)?;
+                if size.iter().any(|&side| side < 2) || bytes.len() != size[0] * size[1] * size[2] {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "expected {} bytes for {}x{}x{} voxels, file has {}",
+                            size[0] * size[1] * size[2],
+                            size[0],
+                            size[1],
+                            size[2],
+                            bytes.len()
+                        ),
+                    ));
+                }
+                (
+                    *size,
+                    bytes.iter().map(|&byte| byte as f32 / 255.0).collect(),
+                )
+            }
+        };
+        let max_density = densities.iter().fold(0.0f32, |max, &d| max.max(d)) as f64;
+        Ok(Self {
+            source,
+            size,
+            densities: Arc::new(densities),
+            max_density,
+            error: None,
+        })
+    }
+
+    /// Grid without any density, for a source that couldn't be loaded
+    fn empty(source: VoxelSource, error: io::Error) -> Self {
+        Self {
+            source,
+            size: [2; 3],
+            densities: Arc::new(vec![0.0; 8]),
+            max_density: 0.0,
+            error: Some(error.to_string()),
+        }
+    }
+
+    pub fn source(&self) -> &VoxelSource {
+        &self.source
+    }
+
+    /// Why the densities couldn't be loaded, the grid is empty then
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Bytes used by the densities
+    pub fn memory_size(&self) -> usize {
+        self.densities.len() * std::mem::size_of::<f32>()
+    }
+
+    /// Trilinearly interpolated density at `p` in [0, 1]³
+    fn density(&self, p: [f64; 3]) -> f64 {
+        let mut base = [0; 3];
+        let mut fraction = [0.0; 3];
+        for axis in 0..3 {
+            // voxel values sit in the cell centers
+            let x =
+                (p[axis] * self.size[axis] as f64 - 0.5).clamp(0.0, (self.size[axis] - 1) as f64);
+            base[axis] = (x as usize).min(self.size[axis] - 2);
+            fraction[axis] = x - base[axis] as f64;
+        }
+
+        let mut density = 0.0;
+        for corner in 0..8 {
+            let mut weight = 1.0;
+            let mut index = 0;
+            for axis in (0..3).rev() {
+                let offset = (corner >> axis) & 1;
+                weight *= match offset {
+                    1 => fraction[axis],
+                    _ => 1.0 - fraction[axis],
+                };
+                index = index * self.size[axis] + base[axis] + offset;
+            }
+            density += weight * self.densities[index] as f64;
+        }
+        density
+    }
+}
+
+impl PartialEq for VoxelGrid {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl From<VoxelSource> for VoxelGrid {
+    fn from(source: VoxelSource) -> Self {
+        Self::new(source.clone()).unwrap_or_else(|error| Self::empty(source, error))
+    }
+}
+
+impl From<VoxelGrid> for VoxelSource {
+    fn from(grid: VoxelGrid) -> Self {
+        grid.source
+    }
+}
+
+///
+/// Heterogeneous participating medium in an axis aligned box with densities from a voxel
+/// grid. Scattering distances are sampled by delta tracking against the grid's largest
+/// density, collisions scatter by the Henyey–Greenstein phase function and emit light.
+///
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoxelVolume {
+    pub min: Point3,
+    pub max: Point3,
+    pub grid: VoxelGrid,
+    /// Density of voxels with the value 1
+    pub density: f64,
+    /// Scattering albedo
+    pub color: Color3,
+    pub emission: Color3,
+    /// Henyey–Greenstein anisotropy
+    pub anisotropy: f64,
+}
+
+impl VoxelVolume {
+    pub fn new(center: Point3, size: f64, grid: VoxelGrid) -> Self {
+        Self {
+            min: center - Vec3::splat(size / 2.0),
+            max: center + Vec3::splat(size / 2.0),
+            grid,
+            density: 10.0,
+            color: Color3::splat(0.8),
+            emission: Color3::splat(0.0),
+            anisotropy: 0.3,
+        }
+    }
+
+    /// Parameter range where the ray is inside the box
+    fn box_interval(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let mut t0 = t_min;
+        let mut t1 = t_max;
+        for axis in 0..3 {
+            let inverse = 1.0 / ray.direction().data[axis];
+            let mut near = (self.min.data[axis] - ray.origin().data[axis]) * inverse;
+            let mut far = (self.max.data[axis] - ray.origin().data[axis]) * inverse;
+            if inverse < 0.0 {
+                std::mem::swap(&mut near, &mut far);
+            }
+            t0 = t0.max(near);
+            t1 = t1.min(far);
+            if t1 <= t0 {
+                return None;
+            }
+        }
+        Some((t0, t1))
+    }
+}
+
+impl Hittable for VoxelVolume {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<Hit> {
+        let (t_enter, t_exit) = self.box_interval(ray, t_min, t_max)?;
+        let majorant = self.density * self.grid.max_density;
+        if majorant <= 0.0 {
+            return None;
+        }
+
+        let extent = self.max - self.min;
+        let ray_length = ray.direction().length();
+        let mut t = t_enter;
+        loop {
+            // tentative collision with the homogenized medium, accepted with probability
+            // of the real density, otherwise it's a null collision and tracking continues.
+            // Each step takes two sampler dimensions.
+            t -= (1.0 - sampler.next_1d()).ln() / (majorant * ray_length);
+            if t >= t_exit {
+                return None;
+            }
+            let point = ray.at(t);
+            let local = (point - self.min) / extent;
+            let density = self.density * self.grid.density(local.data);
+            if sampler.next_1d() * majorant < density {
+                return Some(Hit {
+                    point,
+                    // arbitrary, there is no surface inside the volume
+                    normal: Vec3::new(1.0, 0.0, 0.0),
+                    t,
+                    front_face: true,
+                    uv: [0.0, 0.0],
+                    surface: self.color,
+                    material: Material::HenyeyGreenstein(self.anisotropy),
+                    emission: self.emission,
+                    object: 0,
+                });
+            }
+        }
+    }
+
+    fn intersections(&self, _ray: &Ray) -> Vec<Hit> {
+        vec![]
+    }
+
+    fn name(&self) -> String {
+        format!(
+            "Voxel volume of {} from {} to {}",
+            self.grid.source, self.min, self.max
+        )
+    }
+}
+
+/// Densities of [`VoxelSource::Noise`]
+fn noise_grid(size: [usize; 3], frequency: f64, octaves: u32, seed: u32) -> Vec<f32> {
+    let mut densities = Vec::with_capacity(size[0] * size[1] * size[2]);
+    for z in 0..size[2] {
+        for y in 0..size[1] {
+            for x in 0..size[0] {
+                let p = [
+                    (x as f64 + 0.5) / size[0] as f64,
+                    (y as f64 + 0.5) / size[1] as f64,
+                    (z as f64 + 0.5) / size[2] as f64,
+                ];
+                let mut value = 0.0;
+                let mut amplitude = 0.5;
+                let mut octave_frequency = frequency;
+                for octave in 0..octaves.max(1) {
+                    value += amplitude *
+                        value_noise(p.map(|c| c * octave_frequency), seed.wrapping_add(octave));
+                    amplitude *= 0.5;
+                    octave_frequency *= 2.0;
+                }
+                let radius_squared = p.iter().map(|c| (2.0 * c - 1.0).powi(2)).sum::<f64>();
+                let falloff = (1.0 - radius_squared).max(0.0);
+                densities.push((value * falloff * 2.0).clamp(0.0, 1.0) as f32);
+            }
+        }
+    }
+    densities
+}
+
+/// Smoothly interpolated random values in [0, 1) at the integer lattice
+fn value_noise(p: [f64; 3], seed: u32) -> f64 {
+    let cell = p.map(|c| c.floor());
+    let fraction = [0, 1, 2].map(|axis| {
+        let t = p[axis] - cell[axis];
+        t * t * (3.0 - 2.0 * t)
+    });
+
+    let mut value = 0.0;
+    for corner in 0..8 {
+        let mut weight = 1.0;
+        let mut lattice_hash = seed;
+        for axis in 0..3 {
+            let offset = (corner >> axis) & 1;
+            weight *= match offset {
+                1 => fraction[axis],
+                _ => 1.0 - fraction[axis],
+            };
+            lattice_hash = hash(lattice_hash ^ (cell[axis] as i64 + offset as i64) as u32);
+        }
+        value += weight * hash(lattice_hash) as f64 / u32::MAX as f64;
+    }
+    value
+}
@@ -0,0 +1,64 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+///
+/// Counters collected while rendering a frame
+///
+#[derive(Copy, Clone, Default, Debug)]
+pub struct RenderStats {
+    pub wall_time: Duration,
+    /// Rays cast from the camera, one per sample
+    pub primary_rays: u64,
+    /// Bounce rays continuing the paths
+    pub secondary_rays: u64,
+    /// Rays testing visibility of lights, the renderer has no light sampling yet
+    pub shadow_rays: u64,
+    /// Ray-object intersection tests of all rays
+    pub intersection_tests: u64,
+    /// Bytes used by the scene description
+    pub scene_memory: usize,
+}
+
+impl RenderStats {
+    pub fn total_rays(&self) -> u64 {
+        self.primary_rays + self.secondary_rays + self.shadow_rays
+    }
+
+    pub fn rays_per_second(&self) -> f64 {
+        self.total_rays() as f64 / self.wall_time.as_secs_f64().max(1e-9)
+    }
+
+    /// Average number of segments of a camera path
+    pub fn average_path_length(&self) -> f64 {
+        (self.primary_rays + self.secondary_rays) as f64 / self.primary_rays.max(1) as f64
+    }
+
+    pub fn tests_per_ray(&self) -> f64 {
+        self.intersection_tests as f64 / self.total_rays().max(1) as f64
+    }
+}
+
+impl Display for RenderStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Wall time:            {:.3} s",
+            self.wall_time.as_secs_f64()
+        )?;
+        writeln!(f, "Primary rays:         {}", self.primary_rays)?;
+        writeln!(f, "Secondary rays:       {}", self.secondary_rays)?;
+        writeln!(f, "Shadow rays:          {}", self.shadow_rays)?;
+        writeln!(
+            f,
+            "Rays per second:      {:.2} M",
+            self.rays_per_second() / 1e6
+        )?;
+        writeln!(f, "Average path length:  {:.2}", self.average_path_length())?;
+        writeln!(
+            f,
+            "Intersection tests:   {:.2} per ray",
+            self.tests_per_ray()
+        )?;
+        write!(f, "Scene memory:         {} B", self.scene_memory)
+    }
+}
@@ -1,30 +1,40 @@
 use crate::renderer::camera::Camera;
+use crate::renderer::denoiser::denoise;
+use crate::renderer::film::{Film, SampleFeatures};
+use crate::renderer::frame::Frame;
 use crate::renderer::hittable::Hittable;
-use crate::renderer::scene::Scene;
+use crate::renderer::sampler::Sampler;
+use crate::renderer::scene::{take_intersection_tests, Scene};
+use crate::renderer::stats::RenderStats;
 use crate::renderer::RenderParams;
 use crate::Ray;
 use crate::{Color3, Vec3};
-use egui::{Color32, ColorImage};
-use rand::thread_rng;
-use rand::Rng;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{Receiver, RecvError, Sender};
+use std::sync::Arc;
+use std::time::Instant;
 
 pub enum RenderThreadCommand {
-    //TODO: if the scene grows it should be shared between UI and renderer in RWMutex to prevent copying scene on each frame
-    UpdateScene(Scene),
+    /// Snapshot of the scene, it shares unchanged objects with the UI's scene
+    UpdateScene(Arc<Scene>),
     UpdateRenderParams(RenderParams),
-    RequestFrame,
+    /// Render a frame for the request with the given id
+    RequestFrame(u64),
 }
 
+/// Responses are tagged with id of the request they belong to
 pub enum RenderThreadResponse {
-    FrameRendered(ColorImage),
-    ProgressUpdate(f64),
+    FrameRendered(u64, Frame),
+    ProgressiveFrame(u64, Frame),
+    ProgressUpdate(u64, f64),
 }
 
 pub struct RenderThread {
     pub(crate) sender: Sender<RenderThreadResponse>,
     pub(crate) receiver: Receiver<RenderThreadCommand>,
-    pub(crate) scene: Option<Scene>,
+    /// Id of the newest request sent by the UI
+    pub(crate) latest_request: Arc<AtomicU64>,
+    pub(crate) scene: Option<Arc<Scene>>,
     pub(crate) params: RenderParams,
 }
 
@@ -35,103 +45,218 @@ impl RenderThread {
             match command {
                 RenderThreadCommand::UpdateScene(scene) => self.scene = Some(scene),
                 RenderThreadCommand::UpdateRenderParams(params) => self.params = params,
-                RenderThreadCommand::RequestFrame => {
+                RenderThreadCommand::RequestFrame(request) => {
                     if let Some(scene) = &self.scene {
-                        let render_params = &self.params;
-                        let mut image =
-                            ColorImage::new(render_params.resolution.into(), Color32::BLACK);
-                        self.render(&mut image, render_params, scene);
-                        self.sender
-                            .send(RenderThreadResponse::FrameRendered(image))
-                            .expect("Unable to send response")
+                        if let Some(frame) = self.render(request, &self.params, scene) {
+                            self.sender
+                                .send(RenderThreadResponse::FrameRendered(request, frame))
+                                .expect("Unable to send response")
+                        }
                     }
                 }
             }
         }
     }
 
-    pub fn render(&self, image: &mut ColorImage, params: &RenderParams, scene: &Scene) {
-        let mut rng = thread_rng();
-        let image_width = image.size[0] as f64;
-        let image_height = image.size[1] as f64;
-        let camera = Camera::new(image.size, params.focal_length);
-        let scale = camera.viewport_width / image_width;
+    fn cancelled(&self, request: u64) -> bool {
+        self.latest_request.load(Ordering::SeqCst) != request
+    }
+
+    ///
+    /// Render frame for the given request, returns `None` when a newer request arrived
+    /// before the render finished
+    ///
+    pub fn render(&self, request: u64, params: &RenderParams, scene: &Scene) -> Option<Frame> {
+        let size: [usize; 2] = params.render_resolution().into();
+        let camera = Camera::new(
+            size,
+            &params.camera,
+            params.projection,
+            params.focal_length,
+            params.aperture,
+            params.focus_distance,
+        );
+        let scale = camera.viewport_width / size[0] as f64;
+        let max_samples = params.samples.max(0) as u32;
+        let mut sampler = params.sampler.create(max_samples);
+        let mut film = Film::new(size, params.filter);
 
-        for y in 0..image.size[1] {
-            for x in 0..image.size[0] {
-                let mut cumulated_color = Color3::splat(0.0);
+        let [min, max] = Self::rendered_pixels(params, size);
+        let columns = min[0]..max[0];
+        // film rows go bottom-up, region rows top-down
+        let rows = size[1] - max[1]..size[1] - min[1];
 
-                for _sample in 1..params.samples {
-                    let u = (x as f64 + rng.gen::<f64>()) * scale;
-                    let v = (y as f64 + rng.gen::<f64>()) * scale;
+        let total_samples = (columns.len() * rows.len()) as f64 * max_samples as f64;
+        let start_time = Instant::now();
+        take_intersection_tests();
+        let mut stats = RenderStats {
+            scene_memory: scene.memory_size(),
+            ..RenderStats::default()
+        };
+        let mut samples_done = 0u64;
+        let mut pass_start = 0;
+        while pass_start < max_samples {
+            // progressive passes double the sample count, adaptive ones add a fixed batch
+            let pass_end = if !params.adaptive_sampling {
+                (pass_start * 2).max(1).min(max_samples)
+            } else if pass_start == 0 {
+                (params.min_samples.max(2) as u32).min(max_samples)
+            } else {
+                (pass_start + ADAPTIVE_PASS_SAMPLES).min(max_samples)
+            };
 
-                    let ray = camera.cast_ray(u, v);
-                    let color = Self::ray_color(&ray, scene, params, 0);
-                    cumulated_color = cumulated_color + color;
+            let mut active_pixels = 0;
+            for y in rows.clone() {
+                if self.cancelled(request) {
+                    return None;
                 }
+                for x in columns.clone() {
+                    if params.adaptive_sampling &&
+                        pass_start > 0 &&
+                        film.converged(x, y, params.adaptive_threshold)
+                    {
+                        continue;
+                    }
+                    active_pixels += 1;
+
+                    for sample in pass_start..pass_end {
+                        sampler.start_sample([x, y], sample);
+                        let jitter = sampler.next_2d();
+                        let position = [x as f64 + jitter[0], y as f64 + jitter[1]];
 
-                Self::set_pixel(image, x, y, cumulated_color, params.samples);
+                        let lens = sampler.next_2d();
+                        let time = params.shutter_open +
+                            (params.shutter_close - params.shutter_open) * sampler.next_1d();
+                        let ray =
+                            camera.cast_ray(position[0] * scale, position[1] * scale, lens, time);
+                        let mut features = SampleFeatures::default();
+                        let color = Self::ray_color(
+                            &ray,
+                            scene,
+                            params,
+                            sampler.as_mut(),
+                            &mut features,
+                            0,
+                        );
+                        features.depth = camera.eye_distance(features.depth);
+                        film.add_sample([x, y], position, color, &features);
+                        // every hit continues the path unless the depth limit stops it
+                        stats.primary_rays += 1;
+                        stats.secondary_rays += features.hits.min(MAX_DEPTH as u32) as u64;
+                    }
+                    samples_done += (pass_end - pass_start) as u64;
+                }
+                if y % (size[1] / 50).max(1) == 0 {
+                    self.sender
+                        .send(RenderThreadResponse::ProgressUpdate(
+                            request,
+                            samples_done as f64 / total_samples,
+                        ))
+                        .expect("Unable to comunicate with UI");
+                }
             }
-            if y % (image.size[1] / 50) == 0 {
+
+            if active_pixels == 0 {
+                break;
+            }
+            pass_start = pass_end;
+            if pass_start < max_samples {
                 self.sender
-                    .send(RenderThreadResponse::ProgressUpdate(
-                        (y + 1) as f64 / image_height,
+                    .send(RenderThreadResponse::ProgressiveFrame(
+                        request,
+                        Self::develop(&film, params, &mut stats, start_time),
                     ))
                     .expect("Unable to comunicate with UI");
             }
         }
 
         self.sender
-            .send(RenderThreadResponse::ProgressUpdate(1.0))
+            .send(RenderThreadResponse::ProgressUpdate(request, 1.0))
             .expect("Unable to comunicate with UI");
+        Some(Self::develop(&film, params, &mut stats, start_time))
+    }
+
+    ///
+    /// Pixels `[min, max)` from the top left that get samples: the requested region widened
+    /// by the filter radius, as samples outside of it still splat into its border pixels
+    ///
+    fn rendered_pixels(params: &RenderParams, size: [usize; 2]) -> [[usize; 2]; 2] {
+        let [min, max] = match params.region {
+            Some(region) => region.pixels(size),
+            None => [[0, 0], size],
+        };
+        let margin = params.filter.radius.ceil() as usize;
+        [
+            min.map(|c| c.saturating_sub(margin)),
+            [0, 1].map(|i| (max[i] + margin).min(size[i])),
+        ]
     }
 
-    fn ray_color(ray: &Ray, scene: &Scene, params: &RenderParams, depth: i32) -> Color3 {
-        if depth > 50 {
+    fn develop(
+        film: &Film,
+        params: &RenderParams,
+        stats: &mut RenderStats,
+        start_time: Instant,
+    ) -> Frame {
+        let mut frame = film.frame();
+        frame.region = params.region.map(|region| region.pixels(frame.size()));
+        if params.denoise {
+            let denoised = denoise(&frame, Self::rendered_pixels(params, frame.size()));
+            frame.set_color(denoised);
+        }
+        stats.intersection_tests += take_intersection_tests();
+        stats.wall_time = start_time.elapsed();
+        frame.stats = *stats;
+        frame
+    }
+
+    fn ray_color(
+        ray: &Ray,
+        scene: &Scene,
+        params: &RenderParams,
+        sampler: &mut dyn Sampler,
+        features: &mut SampleFeatures,
+        depth: i32,
+    ) -> Color3 {
+        if depth > MAX_DEPTH {
             return Color3::splat(0.0);
         }
-        let hit = scene.hit(ray, 0.001, f64::INFINITY);
+        let hit = scene.hit(ray, 0.001, f64::INFINITY, sampler);
 
         // let hit_distance = Self::hit_sphere(&center, radius, ray);
         if let Some(the_hit) = hit {
-            let random_bounce = the_hit.point + the_hit.normal + Self::random_in_unit_sphere();
-            // return (the_hit.normal + Color3::splat(1.0)) * 0.5;
-            let new_ray = Ray::new(the_hit.point, random_bounce);
-            return Self::ray_color(&new_ray, scene, params, depth + 1) * 0.5 * the_hit.surface;
+            features.hits += 1;
+            if depth == 0 {
+                features.albedo = the_hit.surface;
+                features.normal = the_hit.outward_normal();
+                features.depth = the_hit.t * ray.direction().length();
+                features.uv = the_hit.uv;
+                features.object = Some(the_hit.object);
+            }
+            let (direction, attenuation) =
+                the_hit
+                    .material
+                    .scatter(ray.direction(), &the_hit, sampler.next_2d());
+            let new_ray = Ray::new(the_hit.point, direction, ray.time());
+            return the_hit.emission +
+                Self::ray_color(&new_ray, scene, params, sampler, features, depth + 1) *
+                    attenuation;
         }
 
         let unit_direction = ray.direction() / ray.direction().length();
         let t = 0.5 * (unit_direction.y() + 1.0);
-        return Color3::splat(1.0).lerp(1.0 - t, &BG_COLOR);
-    }
-
-    fn set_pixel(render_image: &mut ColorImage, x: usize, y: usize, color: Color3, i: i16) {
-        let size = render_image.size;
-        let dest = &mut render_image.pixels[(size[1] - y - 1) * size[0] + x];
-
-        let scale = 1.0 / (i as f64);
-
-        let r = (color.x() * scale).sqrt().clamp(0.0, 1.0);
-        let g = (color.y() * scale).sqrt().clamp(0.0, 1.0);
-        let b = (color.z() * scale).sqrt().clamp(0.0, 1.0);
-
-        dest[0] = fast_round(r * ALMOST_256);
-        dest[1] = fast_round(g * ALMOST_256);
-        dest[2] = fast_round(b * ALMOST_256);
-    }
-
-    fn random_in_unit_sphere() -> Vec3 {
-        let mut rng = thread_rng();
-        Vec3::new(rng.gen::<f64>(), rng.gen::<f64>(), rng.gen::<f64>())
+        let background = Color3::splat(1.0).lerp(1.0 - t, &BG_COLOR);
+        if depth == 0 {
+            features.albedo = background;
+        }
+        background
     }
 }
 
-const ALMOST_256: f64 = 255.999;
+/// Number of bounces after which paths are terminated
+const MAX_DEPTH: i32 = 50;
+/// Number of samples added to pixels that didn't converge yet in each adaptive pass
+const ADAPTIVE_PASS_SAMPLES: u32 = 16;
 static BG_COLOR: Vec3 = Color3 {
     data: [0.5, 0.7, 1.0],
 };
-
-#[inline]
-fn fast_round(r: f64) -> u8 {
-    (r + 0.5).floor() as _ // rust does a saturating cast since 1.45
-}